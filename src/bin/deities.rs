@@ -1,30 +1,213 @@
+use clap::{Parser, Subcommand};
 use colored::*;
 use fs2::FileExt;
-use glob::{glob, Paths};
 use std::{
-    env,
-    fs::File,
-    path,
+    fs::{remove_file, File},
+    path::Path,
+    process::exit,
     sync::{
-        atomic::{AtomicUsize, Ordering},
+        atomic::{AtomicBool, Ordering},
         Arc,
     },
-    thread::{self, sleep, Builder},
-    time::Duration,
+    thread::sleep,
 };
+use signal_hook::consts::{SIGHUP, SIGINT, SIGTERM};
 use tracing_subscriber::{fmt, EnvFilter};
-use users::{Users, UsersCache};
-use uuid::Uuid;
 
 // use users::os::unix::{UserExt, GroupExt};
 // use users::os::bsd::UserExt as BSDUserExt;
 
 use deities::{
-    common::*, init_fields::*, mortal::Mortal, perun::Perun, service::Service, svarog::Svarog,
-    veles::Veles, *,
+    common::*,
+    context::OnShutdown,
+    control::{self, Command as ControlCommand, Request},
+    init_fields::*,
+    mortal::Mortal,
+    perun::Perun,
+    scheduler::Definitions,
+    service::Service,
+    svarog::{human_duration, Svarog},
+    veles::{foreground_exited, Veles},
+    *,
 };
 
 
+/// Deities are three Slavic gods - Perun (supervisor), Veles (service spawner), Svarog (low
+/// level smith)
+#[derive(Parser, Debug)]
+#[command(name = "deities", version, about)]
+struct Args {
+    /// Dir containing services configuration
+    #[arg(
+        long,
+        global = true,
+        env = "DEITIES_SERVICES_DIR",
+        default_value = SERVICES_DIR
+    )]
+    services_dir: String,
+
+    /// Glob match of services configuration files
+    #[arg(
+        long,
+        global = true,
+        env = "DEITIES_SERVICES_GLOB",
+        default_value = SERVICES_GLOB
+    )]
+    services_glob: String,
+
+    /// Lock file of Deities instance [default: /.deities.lock or $HOME/.deities.lock]
+    #[arg(long, global = true, env = "DEITIES_LOCK_FILE")]
+    lock_file: Option<String>,
+
+    /// Control socket of Deities instance [default: lock file path with .sock extension]
+    #[arg(long, global = true, env = "DEITIES_CONTROL_SOCKET")]
+    control_socket: Option<String>,
+
+    /// Address of HTTP endpoint with Prometheus metrics, like: 127.0.0.1:9100 [default:
+    /// disabled]
+    #[arg(long, global = true, env = "DEITIES_METRICS_ADDRESS")]
+    metrics_address: Option<String>,
+
+    /// JSON event log of checks, starts, stops, signals and notifications [default:
+    /// disabled]
+    #[arg(long, global = true, env = "DEITIES_EVENT_LOG")]
+    event_log: Option<String>,
+
+    /// Maximum size of event log in KiB, before it's rotated
+    #[arg(
+        long,
+        global = true,
+        env = "DEITIES_EVENT_LOG_MAX_SIZE",
+        default_value_t = LOG_MAX_SIZE
+    )]
+    event_log_max_size: u64,
+
+    /// Amount of rotated event logs kept
+    #[arg(
+        long,
+        global = true,
+        env = "DEITIES_EVENT_LOG_ROTATE_COUNT",
+        default_value_t = LOG_ROTATE_COUNT
+    )]
+    event_log_rotate_count: usize,
+
+    /// Global notifiers config [default: notifiers.toml in services dir]
+    #[arg(long, global = true, env = "DEITIES_NOTIFIERS_FILE")]
+    notifiers_file: Option<String>,
+
+    /// Handling of supervised services when Deities receives SIGTERM or SIGINT
+    #[arg(
+        long,
+        global = true,
+        env = "DEITIES_ON_SHUTDOWN",
+        value_enum,
+        default_value_t = OnShutdown::Leave
+    )]
+    on_shutdown: OnShutdown,
+
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Supervise all services forever (default)
+    Run,
+
+    /// Perform all checks of service once. Exits with non zero status on failure
+    Check {
+        /// Service name or definition file name
+        service: String,
+    },
+
+    /// Parse service definition file (or all definitions if none given)
+    Validate {
+        /// Path to service definition file
+        file: Option<String>,
+    },
+
+    /// Start service
+    Start {
+        /// Service name or definition file name
+        service: String,
+    },
+
+    /// Stop service
+    Stop {
+        /// Service name or definition file name
+        service: String,
+    },
+
+    /// Stop and start service
+    Restart {
+        /// Service name or definition file name
+        service: String,
+    },
+
+    /// Run 'reload' commands of service
+    Reload {
+        /// Service name or definition file name
+        service: String,
+    },
+
+    /// Show status of services supervised by running Deities
+    Status {
+        /// Service name or definition file name
+        service: Option<String>,
+    },
+
+    /// Pause monitoring of service by running Deities
+    Pause {
+        /// Service name or definition file name
+        service: String,
+    },
+
+    /// Unpause monitoring of service by running Deities
+    Unpause {
+        /// Service name or definition file name
+        service: String,
+    },
+
+    /// Reset restarts of service by running Deities, also after it gave up restarting it
+    Reset {
+        /// Service name or definition file name
+        service: String,
+    },
+
+    /// List discovered services
+    List,
+}
+
+
+impl Args {
+    /// returns runtime context of given arguments
+    fn context(&self) -> Context {
+        let lock_file = self
+            .lock_file
+            .clone()
+            .unwrap_or_else(Context::default_lock);
+        Context {
+            services_dir: self.services_dir.clone(),
+            services_glob: self.services_glob.clone(),
+            control_socket: self
+                .control_socket
+                .clone()
+                .unwrap_or_else(|| Context::default_control_socket(&lock_file)),
+            metrics_address: self.metrics_address.clone(),
+            event_log: self.event_log.clone(),
+            event_log_max_size: self.event_log_max_size,
+            event_log_rotate_count: self.event_log_rotate_count,
+            on_shutdown: self.on_shutdown,
+            notifiers_file: self.notifiers_file.clone(),
+            lock_file,
+            state: State::default(),
+            definitions: Definitions::default(),
+        }
+    }
+}
+
+
 /// Initialize logger and tracingformatter
 #[instrument]
 fn initialize() {
@@ -43,197 +226,479 @@ fn initialize() {
 }
 
 
+/// sends alert notification about failure of service, unless the same alert was already
+/// sent while it's down
 #[instrument]
-fn list_services() -> Paths {
-    glob(&format!("{}/{}", SERVICES_DIR, SERVICES_GLOB))
-        .unwrap_or_else(|_| panic!("Failed to match {}/{}", SERVICES_DIR, SERVICES_GLOB))
+fn alert(
+    context: &Context,
+    service: &Service,
+    failure: &Mortal,
+    message: String,
+    details: String,
+) {
+    if !context.state.should_alert(service, &failure.alert_key()) {
+        debug!(
+            "Alert: {} about: {} already sent. Suppressed.",
+            failure.alert_key(),
+            service
+        );
+        return;
+    }
+    match service.notification(context, failure, message, details) {
+        Ok(msg) => debug!("Notification sent: {}", msg),
+        Err(er) => error!("Error with notification: {}", er),
+    }
 }
 
 
+/// performs checks of service and handles their failure
 #[instrument]
-fn spawn_thread(service_to_monitor: Result<path::PathBuf, glob::GlobError>) {
-    debug!(
-        "Thread UUID: {}",
-        thread::current()
-            .name()
-            .unwrap_or(&Uuid::new_v4().to_string())
-            .bold()
-    );
-    match service_to_monitor.unwrap().file_name() {
-        Some(path) => {
-            match path.to_str() {
-                Some(service_definition_file) => {
-                    match Service::from(service_definition_file.to_string()) {
-                        // perfom Perun checks on service definition:
-                        Ok(service) => {
-                            let interval = service.checks_interval();
-                            debug!("Checks interval: {} ms, of {}", interval, service);
-                            sleep(Duration::from_millis(interval));
-
-                            match service.checks_for() {
-                                Ok(ok) => info!("{}", ok),
-
-                                /* Handle disk space check without any following action */
-                                Err(Mortal::CheckDiskSpace {
-                                    service,
-                                }) => {
-                                    warn!(
-                                        "Service requires: {} MiB free disk space!",
-                                        service.disk_minimum_space() / 1024
-                                    );
-                                    match service.notification(
-                                        format!(
-                                            "Service requires: {} MiB free",
-                                            service.disk_minimum_space() / 1024
-                                        ),
-                                        "Disk space check failure!".to_string(),
-                                    ) {
-                                        Ok(msg) => {
-                                            debug!("Done notification. Result: {}", msg)
-                                        }
-                                        Err(er) => error!("Error with notification: {}", er),
-                                    }
-                                }
-
-                                /* Handle disk inodes check without any following action */
-                                Err(Mortal::CheckDiskInodes {
-                                    service,
-                                }) => {
-                                    warn!(
-                                        "Service requires: {} free inodes!",
-                                        service.disk_minimum_inodes()
-                                    );
-                                    match service.notification(
-                                        format!(
-                                            "Service requires: {} free inodes",
-                                            service.disk_minimum_inodes()
-                                        ),
-                                        "Disk inodes check failure!".to_string(),
-                                    ) {
-                                        Ok(msg) => {
-                                            debug!("Done notification. Result: {}", msg)
-                                        }
-                                        Err(er) => error!("Error with notification: {}", er),
-                                    }
-                                }
-
-                                /*
-                                    NOTE: for other types of failures, we want to handle cleanup/ start routines:
-                                */
-                                Err(error) => {
-                                    warn!(
-                                        "Detected malfunction of: {}. Reason: {}",
-                                        service, error
-                                    );
-                                    match service.notification(
-                                        format!("Detected malfunction of: {}", service),
-                                        error.to_string(),
-                                    ) {
-                                        Ok(msg) => debug!("Notification sent: {}", msg),
-                                        Err(er) => error!("{}", er),
-                                    }
-
-                                    /* notification sent, now try handling service process */
-                                    match service.start_service() {
-                                        Ok(_) => {
-                                            info!(
-                                                "Service started: {}",
-                                                service.name().green().bold()
-                                            )
-                                        }
-                                        Err(cause) => {
-                                            error!(
-                                                "Failed to start service. Reason: {}",
-                                                cause
-                                            )
-                                        }
-                                    }
-                                }
-                            }
-                        }
-
-                        Err(reason) => error!("Definition load failure: {:?}", reason),
+fn supervise(context: Context, service: Service) {
+    service.rotate_logs();
+    if context.state.is_paused(&service.name()) {
+        debug!("Monitoring of: {} is paused", service);
+        return;
+    }
+    let (result, warnings) = service.checks_with_warnings();
+    context
+        .state
+        .record_check(&service.name(), &result, service.read_pid().ok());
+    // NOTE: exit of foreground process was already seen - no need to confirm it by checks:
+    if foreground_exited(&service.name()) {
+        context.state.mark_down(&service.name());
+    }
+    let down = context.state.assess(&service, &result);
+
+    /* Warnings (like expiring certificates) don't affect state of service */
+    context.state.settle_warnings(&service.name(), &warnings);
+    for warning in warnings {
+        warn!("{}", warning);
+        alert(
+            &context,
+            &service,
+            &warning,
+            format!("Warning about: {}", service),
+            warning.to_string(),
+        );
+    }
+
+    match result {
+        Ok(ok) => {
+            info!("{}", ok);
+            if !down {
+                if let Some(downtime) = context.state.take_recovery(&service.name()) {
+                    info!(
+                        "Service recovered: {} after: {}",
+                        service,
+                        human_duration(downtime)
+                    );
+                    match service.recovery_notification(
+                        &context,
+                        format!("Service recovered: {}", service),
+                        downtime,
+                    ) {
+                        Ok(msg) => debug!("Notification sent: {}", msg),
+                        Err(er) => error!("Error with notification: {}", er),
                     }
                 }
+            }
+        }
+
+        /* Tolerate failures until failure_threshold of the same check is reached */
+        Err(failure) if !down => {
+            warn!("Tolerated check failure of: {}. Reason: {}", service, failure)
+        }
+
+        /* Handle warnings (like low disk space) without any following action */
+        Err(failure) if failure.is_warning() => {
+            warn!("{}", failure);
+            alert(
+                &context,
+                &service,
+                &failure,
+                format!("Warning about: {}", service),
+                failure.to_string(),
+            );
+        }
 
-                None => error!("Unable to open service file in path: {:?}", path),
+        /* Handle failures which can't be fixed by restart without any following action */
+        Err(failure) if !failure.should_restart() => {
+            error!("Failure of: {}. Reason: {}", service, failure);
+            alert(
+                &context,
+                &service,
+                &failure,
+                format!("Detected failure of: {}", service),
+                failure.to_string(),
+            );
+        }
+
+        /*
+            NOTE: for other types of failures, we want to handle cleanup/ start routines:
+        */
+        Err(error) => {
+            // NOTE: service can't work without its dependencies, so don't restart nor notify:
+            if let Err(waiting) = context.dependencies_ready(&service) {
+                info!("{}", waiting);
+                context.state.record_failure(&service.name(), &waiting);
+                context.state.update(&service.name(), |state| {
+                    state.last_check = Some(waiting.to_string())
+                });
+                return;
+            }
+            if context.state.is_given_up(&service.name()) {
+                debug!("Gave up restarting: {}. Waiting for reset.", service);
+                return;
+            }
+            warn!("Detected malfunction of: {}. Reason: {}", service, error);
+            let output = service.output_tail();
+            let details = match output.len() {
+                0 => error.to_string(),
+                _ => {
+                    format!("{}\nLast output:\n{}", error, output.join("\n"))
+                }
+            };
+
+            /* crash loop protection - final notification once Deities gives up */
+            match context.state.restart_permitted(&service) {
+                Ok(_) => trace!("Restart permitted for: {}", service),
+                Err(
+                    backed_off @ Mortal::ServiceRestartBackedOff {
+                        ..
+                    },
+                ) => {
+                    debug!("{}", backed_off);
+                    return;
+                }
+                Err(given_up) => {
+                    error!("{}", given_up);
+                    context.state.record_failure(&service.name(), &given_up);
+                    alert(
+                        &context,
+                        &service,
+                        &given_up,
+                        format!("Gave up restarting: {}", service),
+                        format!("{}\n{}", given_up, details),
+                    );
+                    return;
+                }
+            }
+
+            alert(
+                &context,
+                &service,
+                &error,
+                format!("Detected malfunction of: {}", service),
+                details,
+            );
+
+            /* notification sent, now try handling service process */
+            context.state.record_restart_attempt(&service);
+            match service.start_service() {
+                Ok(pid) => {
+                    context.state.record_restart(&service.name(), pid);
+                    info!("Service started: {}", service.name().green().bold())
+                }
+                Err(cause) => {
+                    error!("Failed to start service. Reason: {}", cause);
+                    context.state.record_failure(&service.name(), &cause);
+                    alert(
+                        &context,
+                        &service,
+                        &cause,
+                        format!("Failed to start: {}", service),
+                        cause.to_string(),
+                    );
+                }
             }
         }
+    }
+}
+
 
-        None => error!("No access to read service definition file?"),
+/// runs 'reload' commands of given services
+#[instrument]
+fn reload_services(services: Vec<Service>) {
+    for service in services {
+        match service.reload_service() {
+            Ok(ok) => info!("{}", ok),
+            Err(Mortal::ServiceNoReloadDefined {
+                service,
+            }) => trace!("Reload skipped for: {}", service),
+            Err(err) => error!("{}", err),
+        }
     }
 }
 
 
 #[instrument]
-fn eternity() -> () {
-    let cycle_count = Arc::new(AtomicUsize::new(0));
-    loop {
-        cycle_count.fetch_add(1, Ordering::SeqCst);
-        debug!(
-            "Iteration no. {}",
-            format!("{}", cycle_count.clone().load(Ordering::SeqCst))
-                .yellow()
-                .bold()
+fn eternity(
+    context: Context,
+    reload_requested: Arc<AtomicBool>,
+    shutdown_requested: Arc<AtomicBool>,
+) {
+    let mut scheduler = Scheduler::new(context.clone(), supervise);
+    if let Err(cause) = scheduler.watch() {
+        warn!(
+            "Can't watch {} for changes, definitions will be reloaded only on SIGHUP. \
+             Reason: {}",
+            context.services_dir, cause
         );
+    }
+    let mut tick_count: usize = 0;
+    while !shutdown_requested.load(Ordering::SeqCst) {
+        if reload_requested.swap(false, Ordering::SeqCst) {
+            info!("SIGHUP received. Reloading definitions and services.");
+            scheduler.reload();
+            reload_services(scheduler.services());
+        }
+        tick_count += 1;
+        trace!("Tick no. {}", format!("{}", tick_count).yellow().bold());
+        sleep(scheduler.tick())
+    }
+    info!("Shutdown requested.");
+    scheduler.shutdown()
+}
 
-        // let handlers: Vec<thread::JoinHandle<_>> =
-        let out: String = list_services()
-            .flat_map(|service_to_monitor| {
-                let thread_builder = Builder::new().name(Uuid::new_v4().to_string());
-                thread_builder.spawn(|| spawn_thread(service_to_monitor))
-            })
-            .map(|handle| {
-                let name = format!("{}", handle.thread().name().unwrap_or("Unnamed"));
-                (handle, name)
-            })
-            .map(|(handle, name)| {
-                match handle.join() {
-                    Ok(_) => {
-                        format!(
-                            "Thread: {} joined iteration: {}",
-                            name,
-                            cycle_count.load(Ordering::SeqCst)
-                        )
-                    }
-                    Err(cause) => {
-                        format!(
-                            "Thread: {} failed to join iteration: {}! Internal cause: {:?}",
-                            name,
-                            cycle_count.load(Ordering::SeqCst),
-                            cause
-                        )
-                    }
-                }
-            })
-            .collect();
 
-        match &out[..] {
-            "" => {
-                debug!("No services found under /Services, throttling..");
-                sleep(Duration::from_millis(CHECKS_INTERVAL))
-            }
-            traced => trace!("{}", traced),
+/// prints result of a command and exits with status reflecting it
+fn finish(result: Result<String, Mortal>) -> ! {
+    match result {
+        Ok(message) => {
+            println!("{}", message);
+            exit(0)
+        }
+        Err(failure) => {
+            eprintln!("{}", failure);
+            exit(1)
         }
     }
 }
 
 
+/// sends command to running Deities. Returns None if it's not running
 #[instrument]
-fn main() {
-    initialize();
+fn remote(
+    context: &Context,
+    command: ControlCommand,
+    service: Option<&str>,
+) -> Option<Result<String, Mortal>> {
+    let request = Request {
+        command,
+        service: service.map(|name| name.to_string()),
+    };
+    match control::request(&context.control_socket, &request) {
+        Ok(response) if response.ok => {
+            let states: Vec<String> = response
+                .services
+                .iter()
+                .map(|state| {
+                    format!(
+                        "{}: {}, pid: {}, restarts: {}, last restart: {}{}{}",
+                        state.name,
+                        match (state.down, state.last_check_ok) {
+                            (true, _) => "down",
+                            (false, Some(true)) => "ok",
+                            (false, Some(false)) => "failing",
+                            (false, None) => "unchecked",
+                        },
+                        state.pid.map(|pid| pid.to_string()).unwrap_or_default(),
+                        state.restarts,
+                        state.last_restart_at.clone().unwrap_or_default(),
+                        if state.paused { " (paused)" } else { "" },
+                        if state.given_up { " (given up)" } else { "" }
+                    )
+                })
+                .collect();
+            Some(Ok([vec![response.message], states].concat().join("\n")))
+        }
+        Ok(response) => {
+            Some(Err(Mortal::ControlRequestFailure {
+                message: response.message,
+            }))
+        }
+        Err(cause) => {
+            debug!(
+                "No Deities listening on: {}. Reason: {}",
+                context.control_socket, cause
+            );
+            None
+        }
+    }
+}
+
+
+/// sends command to running Deities. Fails if it's not running
+#[instrument]
+fn remote_only(
+    context: &Context,
+    command: ControlCommand,
+    service: Option<&str>,
+) -> Result<String, Mortal> {
+    remote(context, command, service).unwrap_or_else(|| {
+        Err(Mortal::ControlSocketUnavailable {
+            control_socket: context.control_socket.clone(),
+        })
+    })
+}
+
 
-    let users = UsersCache::new();
-    let lock_name = match users.get_current_uid() {
-        0 => DEFAULT_LOCK.to_string(),
-        _ => {
-            format!(
-                "{}{}",
-                env::var("HOME").unwrap_or_else(|_| "/tmp".to_string()),
-                DEFAULT_LOCK
-            )
+#[instrument]
+fn check(context: &Context, name: &str) -> Result<String, Mortal> {
+    if let Some(result) = remote(context, ControlCommand::Check, Some(name)) {
+        return result;
+    }
+    let service = context.service(name)?;
+    service.checks_for().map(|ok| ok.to_string())
+}
+
+
+#[instrument]
+fn validate(context: &Context, file: Option<String>) -> Result<String, Mortal> {
+    let results = match file {
+        Some(file) => {
+            let path = Path::new(&file);
+            let services_dir = match path.parent() {
+                Some(dir) if !dir.as_os_str().is_empty() => dir.to_string_lossy().to_string(),
+                _ => ".".to_string(),
+            };
+            let file_name = path
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_default();
+            vec![Service::from(
+                &context.clone().with_services_dir(&services_dir),
+                file_name,
+            )]
         }
+        None => context.services(),
     };
+    let total = results.len();
+    let mut valid = vec![];
+    for result in results {
+        match result.and_then(|service| context.notifiers_for(&service).map(|_| service)) {
+            Ok(service) => valid.push(format!("Valid: {}", service)),
+            Err(cause) => eprintln!("{}", cause),
+        }
+    }
+    match total - valid.len() {
+        0 => Ok(valid.join("\n")),
+        invalid => {
+            Err(Mortal::SanityCheckFailure {
+                message: format!("{} of {} definitions are invalid!", invalid, total),
+            })
+        }
+    }
+}
+
+
+/// checks required dependencies of service once, before it's started by command.
+/// Without running Deities there are no results of their last checks to rely on
+#[instrument]
+fn dependencies_ready(context: &Context, service: &Service) -> Result<(), Mortal> {
+    for name in service.requires() {
+        match context.service(&name) {
+            Ok(dependency) => {
+                context.state.record_check(
+                    &name,
+                    &dependency.checks_for(),
+                    dependency.read_pid().ok(),
+                )
+            }
+            Err(failure) => {
+                context.state.update(&name, |state| {
+                    state.last_check = Some(failure.to_string());
+                    state.last_check_ok = Some(false);
+                })
+            }
+        }
+    }
+    context.dependencies_ready(service)
+}
+
+
+/// refuses services which can't be started by command, without running Deities.
+/// Foreground process would be left behind without anyone to reap it or read its output
+#[instrument]
+fn unsupervised(service: Service) -> Result<Service, Mortal> {
+    if service.foreground() {
+        return Err(Mortal::ServiceForegroundUnsupervised {
+            service: Box::new(service),
+        });
+    }
+    Ok(service)
+}
+
+
+#[instrument]
+fn start(context: &Context, name: &str) -> Result<String, Mortal> {
+    if let Some(result) = remote(context, ControlCommand::Start, Some(name)) {
+        return result;
+    }
+    let service = unsupervised(context.service(name)?)?;
+    dependencies_ready(context, &service)?;
+    service
+        .start_service()
+        .map(|pid| format!("Started: {} with pid: {}", service, pid))
+}
+
+
+#[instrument]
+fn stop(context: &Context, name: &str) -> Result<String, Mortal> {
+    if let Some(result) = remote(context, ControlCommand::Stop, Some(name)) {
+        return result;
+    }
+    let service = context.service(name)?;
+    service.stop_service().map(|ok| ok.to_string())
+}
+
+
+#[instrument]
+fn restart(context: &Context, name: &str) -> Result<String, Mortal> {
+    if let Some(result) = remote(context, ControlCommand::Restart, Some(name)) {
+        return result;
+    }
+    let service = unsupervised(context.service(name)?)?;
+    dependencies_ready(context, &service)?;
+    match service.stop_service() {
+        Ok(ok) => info!("{}", ok),
+        Err(cause) => warn!("Stop failed, starting anyway. Reason: {}", cause),
+    }
+    service
+        .start_service()
+        .map(|pid| format!("Restarted: {} with pid: {}", service, pid))
+}
+
+
+#[instrument]
+fn reload(context: &Context, name: &str) -> Result<String, Mortal> {
+    if let Some(result) = remote(context, ControlCommand::Reload, Some(name)) {
+        return result;
+    }
+    let service = context.service(name)?;
+    service.reload_service().map(|ok| ok.to_string())
+}
+
+
+#[instrument]
+fn list(context: &Context) -> Result<String, Mortal> {
+    Ok(context
+        .services()
+        .into_iter()
+        .map(|result| {
+            match result {
+                Ok(service) => service.to_string(),
+                Err(cause) => format!("Broken: {}", cause),
+            }
+        })
+        .collect::<Vec<String>>()
+        .join("\n"))
+}
+
+
+#[instrument]
+fn run(context: Context) {
+    let lock_name = context.lock_file.clone();
 
     let lockfile = match File::open(lock_name.clone()) {
         Ok(file) => file,
@@ -260,6 +725,101 @@ fn main() {
         }
     }
 
+    if let Err(cause) = control::serve(context.clone()) {
+        error!(
+            "Failed to listen on control socket: {}. Reason: {}",
+            context.control_socket, cause
+        );
+    }
+
+    if let Some(address) = context.metrics_address.as_ref() {
+        if let Err(cause) = metrics::serve(address) {
+            error!(
+                "Failed to serve metrics on: {}. Reason: {}",
+                address, cause
+            );
+        }
+    }
+
+    if let Some(path) = context.event_log.as_ref() {
+        if let Err(cause) = event_log::open(
+            path,
+            context.event_log_max_size,
+            context.event_log_rotate_count,
+        ) {
+            error!("Failed to open event log: {}. Reason: {}", path, cause);
+        }
+    }
+
+    let reload_requested = Arc::new(AtomicBool::new(false));
+    if let Err(cause) = signal_hook::flag::register(SIGHUP, reload_requested.clone()) {
+        error!("Failed to register SIGHUP handler! Reason: {}", cause);
+    }
+
+    let shutdown_requested = Arc::new(AtomicBool::new(false));
+    for signal in [SIGTERM, SIGINT] {
+        // NOTE: second signal terminates immediately, in case graceful shutdown hangs:
+        let registered = signal_hook::flag::register_conditional_shutdown(
+            signal,
+            1,
+            shutdown_requested.clone(),
+        )
+        .and_then(|_| signal_hook::flag::register(signal, shutdown_requested.clone()));
+        if let Err(cause) = registered {
+            error!("Failed to register handler of signal: {}! Reason: {}", signal, cause);
+        }
+    }
+
     info!("{} v{}", NAME.green().bold(), VERSION.yellow().bold());
-    eternity()
+    eternity(context.clone(), reload_requested, shutdown_requested);
+
+    let _ = remove_file(&context.control_socket);
+    match FileExt::unlock(&lockfile) {
+        Ok(_) => info!("Lock file released: {}", lock_name),
+        Err(cause) => error!("Failed to release lock file! Reason: {}", cause),
+    }
+    info!("{} stopped.", NAME);
+}
+
+
+#[instrument]
+fn main() {
+    let args = Args::parse();
+    let context = args.context();
+    initialize();
+
+    match args.command {
+        None | Some(Command::Run) => run(context),
+        Some(Command::Check {
+            service,
+        }) => finish(check(&context, &service)),
+        Some(Command::Validate {
+            file,
+        }) => finish(validate(&context, file)),
+        Some(Command::Start {
+            service,
+        }) => finish(start(&context, &service)),
+        Some(Command::Stop {
+            service,
+        }) => finish(stop(&context, &service)),
+        Some(Command::Restart {
+            service,
+        }) => finish(restart(&context, &service)),
+        Some(Command::Reload {
+            service,
+        }) => finish(reload(&context, &service)),
+        Some(Command::List) => finish(list(&context)),
+        Some(Command::Status {
+            service,
+        }) => finish(remote_only(&context, ControlCommand::Status, service.as_deref())),
+        Some(Command::Pause {
+            service,
+        }) => finish(remote_only(&context, ControlCommand::Pause, Some(&service))),
+        Some(Command::Unpause {
+            service,
+        }) => finish(remote_only(&context, ControlCommand::Unpause, Some(&service))),
+        Some(Command::Reset {
+            service,
+        }) => finish(remote_only(&context, ControlCommand::Reset, Some(&service))),
+    }
 }
@@ -6,20 +6,25 @@ pub const NAME: &str = "Deities";
 /// project version from cargo metadata
 pub const VERSION: &str = env!("CARGO_PKG_VERSION");
 
-/// Default dir containing services configuration
+/// Default dir containing services configuration (see Context)
 pub static SERVICES_DIR: &str = "/Services";
 
 /// Default glob match for file types we want to process as services configuration
 pub static SERVICES_GLOB: &str = "*.ini";
 
 /// default message contents sent via UNIX socket after connection
-pub static UNIX_SOCKET_MSG: &[u8; 7] = b"version";
+pub static UNIX_SOCKET_MSG: &str = "version";
 
 /// default logger level env variable
 pub static LOG_ENV: &str = "LOG";
 
-/// default path to system cacert.pem (used by curl)
-pub static CACERT_PEM: &str = "/etc/ssl/cert.pem";
+/// default paths to system cacert.pem (first existing one is used by curl)
+pub static CACERT_PEMS: [&str; 4] = [
+    "/etc/ssl/cert.pem",                      // BSD, Darwin
+    "/etc/ssl/certs/ca-certificates.crt",     // Debian, Ubuntu, Alpine
+    "/etc/pki/tls/certs/ca-bundle.crt",       // Fedora, RHEL
+    "/usr/local/share/certs/ca-root-nss.crt", // FreeBSD ports
+];
 
 /// default host to report as a fallback
 pub static DEFAULT_HOSTNAME: &str = "localhost";
@@ -37,6 +42,12 @@ pub static DEFAULT_SHELL: &str = "/bin/sh";
 pub static DEFAULT_PATH: &str = "/bin:/usr/bin:/sbin:/usr/sbin:/usr/local/bin:\
                                          /usr/local/sbin";
 
+/// default name of global notifiers config file - in services dir
+pub static NOTIFIERS_FILE: &str = "notifiers.toml";
+
+/// timeout of notification delivery (in ms)
+pub static NOTIFICATION_TIMEOUT: u64 = 10000;
+
 /// default lock file
 pub static DEFAULT_LOCK: &str = "/.deities.lock";
 
@@ -48,14 +59,57 @@ pub static CHECKS_INTERVAL: u64 = 3000;
 /// timeouts for connection, transfer and dns cache for curl
 pub static CHECKS_URL_TIMEOUT: u64 = 10000;
 
+/// timeout of connection for TCP check
+pub static CHECKS_TCP_TIMEOUT: u64 = 3000;
+
+/// timeout of sending message and reading expected response in UNIX socket and TCP checks
+pub static CHECKS_RESPONSE_TIMEOUT: u64 = 3000;
+
+/// maximum size of response read in UNIX socket and TCP checks (in bytes)
+pub static CHECKS_RESPONSE_MAX_SIZE: usize = 65536;
+
+/// warn about TLS certificates of urls expiring within given amount of days (0 - disabled)
+pub static TLS_EXPIRY_WARN_DAYS: u64 = 0;
+
 /// pause after each signal sent by death_watch to get rid of live pid
 pub static DEATHWATCHES_INTERVAL: u64 = 2000;
 
+/// maximum run time of lifecycle hook commands, before they're killed (in ms)
+pub static HOOK_TIMEOUT: u64 = 60000;
+
 /// minimum disk space required for disk
 pub static DISK_MINIMUM_SPACE: i64 = 3000; // in MiB
 
 /// minimum disk inodes on disk
 pub static DISK_MINIMUM_INODES: i64 = 4096;
 
+/// maximum size of service log file before rotation (in KiB)
+pub static LOG_MAX_SIZE: u64 = 10240;
+
+/// amount of rotated service log files to keep
+pub static LOG_ROTATE_COUNT: usize = 5;
+
+/// amount of last service log lines attached to failure reports
+pub static LOG_TAIL_LINES: usize = 20;
+
+/// amount of failures of the same check in a row, before service is considered down
+pub static FAILURE_THRESHOLD: u64 = 1;
+
+/// amount of passed checks in a row, before service is considered recovered
+pub static SUCCESS_THRESHOLD: u64 = 1;
+
+/// maximum amount of automatic restarts within restarts window, before Deities gives up
+pub static MAX_RESTARTS: usize = 5;
+
+/// time window of counted automatic restarts (in ms)
+pub static RESTARTS_WINDOW: u64 = 300000;
+
+/// maximum pause between automatic restarts (in ms)
+pub static RESTART_BACKOFF_MAX: u64 = 60000;
+
+/// pause before repeating the same alert about service which stays down (in ms, 0 disables
+/// reminders)
+pub static ALERT_REMINDER_INTERVAL: u64 = 0;
+
 /// default channel to post notifications
 pub static SLACK_ALERT_CHANNEL: &str = "#ops-status";
@@ -0,0 +1,334 @@
+use clap::ValueEnum;
+use glob::glob;
+use std::{env, path::PathBuf};
+use users::{Users, UsersCache};
+
+use crate::{
+    init_fields::InitFields,
+    mortal::Mortal::{self, *},
+    notifier::{self, NotifierConfig, SlackNotifier},
+    scheduler::Definitions,
+    service::Service,
+    *,
+};
+
+
+/// handling of supervised services when Deities shuts down
+#[derive(
+    Serialize, Deserialize, ValueEnum, Debug, Clone, Copy, Default, PartialEq, Eq,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum OnShutdown {
+    /// leave services running
+    #[default]
+    Leave,
+
+    /// stop all services, in reverse order of their start
+    Stop,
+}
+
+
+// Context is a runtime configuration of single Deities instance.
+// It's passed around instead of global paths, so library can be embedded with any root.
+//
+
+#[derive(Debug, Clone)]
+pub struct Context {
+    /// dir containing services configuration
+    pub services_dir: String,
+
+    /// glob match for file types we want to process as services configuration
+    pub services_glob: String,
+
+    /// path to lock file of Deities instance
+    pub lock_file: String,
+
+    /// path to control socket of Deities instance
+    pub control_socket: String,
+
+    /// address of HTTP metrics endpoint (disabled if unset)
+    pub metrics_address: Option<String>,
+
+    /// path to JSON event log (disabled if unset)
+    pub event_log: Option<String>,
+
+    /// maximum size of event log in KiB, before it's rotated
+    pub event_log_max_size: u64,
+
+    /// amount of rotated event logs kept
+    pub event_log_rotate_count: usize,
+
+    /// handling of supervised services on shutdown
+    pub on_shutdown: OnShutdown,
+
+    /// path to global notifiers config (NOTIFIERS_FILE in services dir if unset)
+    pub notifiers_file: Option<String>,
+
+    /// runtime status of supervised services
+    pub state: State,
+
+    /// last known-good definitions of supervised services
+    pub definitions: Definitions,
+}
+
+
+impl Default for Context {
+    /// context with defaults, overridable with DEITIES_SERVICES_DIR, DEITIES_SERVICES_GLOB,
+    /// DEITIES_LOCK_FILE, DEITIES_CONTROL_SOCKET, DEITIES_METRICS_ADDRESS,
+    /// DEITIES_EVENT_LOG, DEITIES_EVENT_LOG_MAX_SIZE, DEITIES_EVENT_LOG_ROTATE_COUNT,
+    /// DEITIES_ON_SHUTDOWN and DEITIES_NOTIFIERS_FILE env variables
+    fn default() -> Context {
+        let lock_file =
+            env::var("DEITIES_LOCK_FILE").unwrap_or_else(|_| Context::default_lock());
+        Context {
+            services_dir: env::var("DEITIES_SERVICES_DIR")
+                .unwrap_or_else(|_| SERVICES_DIR.to_string()),
+            services_glob: env::var("DEITIES_SERVICES_GLOB")
+                .unwrap_or_else(|_| SERVICES_GLOB.to_string()),
+            control_socket: env::var("DEITIES_CONTROL_SOCKET")
+                .unwrap_or_else(|_| Context::default_control_socket(&lock_file)),
+            metrics_address: env::var("DEITIES_METRICS_ADDRESS").ok(),
+            event_log: env::var("DEITIES_EVENT_LOG").ok(),
+            event_log_max_size: env::var("DEITIES_EVENT_LOG_MAX_SIZE")
+                .ok()
+                .and_then(|size| size.parse().ok())
+                .unwrap_or(LOG_MAX_SIZE),
+            event_log_rotate_count: env::var("DEITIES_EVENT_LOG_ROTATE_COUNT")
+                .ok()
+                .and_then(|count| count.parse().ok())
+                .unwrap_or(LOG_ROTATE_COUNT),
+            on_shutdown: env::var("DEITIES_ON_SHUTDOWN")
+                .ok()
+                .and_then(|policy| OnShutdown::from_str(&policy, true).ok())
+                .unwrap_or_default(),
+            notifiers_file: env::var("DEITIES_NOTIFIERS_FILE").ok(),
+            lock_file,
+            state: State::default(),
+            definitions: Definitions::default(),
+        }
+    }
+}
+
+
+impl Context {
+    /// creates context with services in given dir (other values default)
+    pub fn new(services_dir: &str) -> Context {
+        Context {
+            services_dir: services_dir.to_string(),
+            ..Context::default()
+        }
+    }
+
+
+    /// sets dir containing services configuration
+    pub fn with_services_dir(self, services_dir: &str) -> Context {
+        Context {
+            services_dir: services_dir.to_string(),
+            ..self
+        }
+    }
+
+
+    /// sets glob match of services configuration files
+    pub fn with_services_glob(self, services_glob: &str) -> Context {
+        Context {
+            services_glob: services_glob.to_string(),
+            ..self
+        }
+    }
+
+
+    /// sets path to lock file
+    pub fn with_lock_file(self, lock_file: &str) -> Context {
+        Context {
+            lock_file: lock_file.to_string(),
+            ..self
+        }
+    }
+
+
+    /// sets path to control socket
+    pub fn with_control_socket(self, control_socket: &str) -> Context {
+        Context {
+            control_socket: control_socket.to_string(),
+            ..self
+        }
+    }
+
+
+    /// default lock file - in root dir for superuser, in HOME for others
+    #[instrument]
+    pub fn default_lock() -> String {
+        let users = UsersCache::new();
+        match users.get_current_uid() {
+            0 => DEFAULT_LOCK.to_string(),
+            _ => {
+                format!(
+                    "{}{}",
+                    env::var("HOME").unwrap_or_else(|_| "/tmp".to_string()),
+                    DEFAULT_LOCK
+                )
+            }
+        }
+    }
+
+
+    /// sets address of HTTP metrics endpoint
+    pub fn with_metrics_address(self, metrics_address: &str) -> Context {
+        Context {
+            metrics_address: Some(metrics_address.to_string()),
+            ..self
+        }
+    }
+
+
+    /// sets path to JSON event log
+    pub fn with_event_log(self, event_log: &str) -> Context {
+        Context {
+            event_log: Some(event_log.to_string()),
+            ..self
+        }
+    }
+
+
+    /// sets maximum size of event log in KiB and amount of its rotated files kept
+    pub fn with_event_log_rotation(self, max_size: u64, rotate_count: usize) -> Context {
+        Context {
+            event_log_max_size: max_size,
+            event_log_rotate_count: rotate_count,
+            ..self
+        }
+    }
+
+
+    /// sets handling of supervised services on shutdown
+    pub fn with_on_shutdown(self, on_shutdown: OnShutdown) -> Context {
+        Context {
+            on_shutdown,
+            ..self
+        }
+    }
+
+
+    /// sets path to global notifiers config
+    pub fn with_notifiers_file(self, notifiers_file: &str) -> Context {
+        Context {
+            notifiers_file: Some(notifiers_file.to_string()),
+            ..self
+        }
+    }
+
+
+    /// returns path to global notifiers config
+    pub fn notifiers_file(&self) -> String {
+        self.notifiers_file
+            .clone()
+            .unwrap_or_else(|| format!("{}/{}", self.services_dir, NOTIFIERS_FILE))
+    }
+
+
+    /// returns named notifiers which service sends notifications with.
+    /// Service without notifiers sends them with its Slack settings
+    #[instrument]
+    pub fn notifiers_for(
+        &self,
+        service: &Service,
+    ) -> Result<Vec<(String, NotifierConfig)>, Mortal> {
+        let names = service.notifiers();
+        if names.is_empty() {
+            return Ok(vec![(
+                "slack".to_string(),
+                NotifierConfig::Slack(SlackNotifier {
+                    webhook_url: service.slack_webhook_url(),
+                    channel: Some(service.slack_alert_channel()),
+                }),
+            )]);
+        }
+        let notifiers = notifier::load(&self.notifiers_file())?;
+        names
+            .into_iter()
+            .map(|name| {
+                match notifiers.get(&name) {
+                    Some(config) => Ok((name, config.clone())),
+                    None => {
+                        Err(NotifierUnknown {
+                            service: Box::new(service.clone()),
+                            notifier: name,
+                        })
+                    }
+                }
+            })
+            .collect()
+    }
+
+
+    /// default control socket - next to the lock file
+    pub fn default_control_socket(lock_file: &str) -> String {
+        format!("{}.sock", lock_file.trim_end_matches(".lock"))
+    }
+
+
+    /// returns paths to all service definitions
+    #[instrument]
+    pub fn list_services(&self) -> Vec<PathBuf> {
+        match glob(&format!("{}/{}", self.services_dir, self.services_glob)) {
+            Ok(paths) => paths.flatten().collect(),
+            Err(cause) => {
+                error!(
+                    "Failed to match {}/{}. Reason: {}",
+                    self.services_dir, self.services_glob, cause
+                );
+                vec![]
+            }
+        }
+    }
+
+
+    /// loads all service definitions
+    #[instrument]
+    pub fn services(&self) -> Vec<Result<Service, Mortal>> {
+        self.list_services()
+            .iter()
+            .filter_map(|path| path.file_name().and_then(|name| name.to_str()))
+            .map(|file_name| Service::from(self, file_name.to_string()))
+            .collect()
+    }
+
+
+    /// loads definition of service with given name (or definition file name)
+    #[instrument]
+    pub fn service(&self, name: &str) -> Result<Service, Mortal> {
+        self.services()
+            .into_iter()
+            .flatten()
+            .find(|service| service.name() == name || service.ini_file() == name)
+            .ok_or_else(|| {
+                ServiceNotFound {
+                    name: name.to_string(),
+                    services_dir: self.services_dir.clone(),
+                }
+            })
+    }
+
+
+    /// checks required dependencies of service with their last known checks result.
+    /// Fails with first required service which didn't pass its last checks.
+    /// Services in 'after' only define start order - they're not awaited
+    #[instrument]
+    pub fn dependencies_ready(&self, service: &Service) -> Result<(), Mortal> {
+        for name in service.requires() {
+            let dependency = self.state.get(&name);
+            let reason = match (dependency.last_check_ok, dependency.last_check) {
+                (Some(true), _) => continue,
+                (_, Some(last_check)) => last_check,
+                (_, None) => "Not checked yet".to_string(),
+            };
+            return Err(ServiceWaitingForDependency {
+                service: Box::new(service.clone()),
+                dependency: name,
+                reason,
+            });
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,295 @@
+use serde_json::Value;
+use std::{
+    fs::remove_file,
+    io::{prelude::*, BufReader, Error},
+    os::unix::net::{UnixListener, UnixStream},
+    thread::Builder,
+};
+
+use crate::{
+    context::Context,
+    mortal::{Blessing, Mortal},
+    perun::Perun,
+    service::Service,
+    state::ServiceState,
+    svarog::Svarog,
+    veles::Veles,
+    *,
+};
+
+
+/// commands accepted through control socket
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Command {
+    Status,
+    Check,
+    Start,
+    Stop,
+    Restart,
+    Reload,
+    Pause,
+    Unpause,
+    Reset,
+}
+
+
+/// single line JSON request sent to control socket
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Request {
+    pub command: Command,
+
+    /// service name. Required by all commands except status
+    #[serde(default)]
+    pub service: Option<String>,
+}
+
+
+/// single line JSON response sent back by control socket
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct Response {
+    pub ok: bool,
+    pub message: String,
+
+    /// serialized Mortal result of command (if any)
+    #[serde(default)]
+    pub mortal: Option<Value>,
+
+    #[serde(default)]
+    pub services: Vec<ServiceState>,
+}
+
+
+impl Response {
+    fn from(result: Result<Blessing, Mortal>) -> Response {
+        match result {
+            Ok(ok) => {
+                Response {
+                    ok: true,
+                    message: ok.to_string(),
+                    mortal: serde_json::to_value(&ok).ok(),
+                    ..Response::default()
+                }
+            }
+            Err(failure) => {
+                Response {
+                    ok: false,
+                    message: failure.to_string(),
+                    mortal: serde_json::to_value(&failure).ok(),
+                    ..Response::default()
+                }
+            }
+        }
+    }
+}
+
+
+/// starts control socket server thread
+#[instrument]
+pub fn serve(context: Context) -> Result<(), Error> {
+    // NOTE: we hold the lock file, so any existing socket is a leftover:
+    let _ = remove_file(&context.control_socket);
+    let listener = UnixListener::bind(&context.control_socket)?;
+    info!("Control socket listening on: {}", context.control_socket);
+    Builder::new()
+        .name("control".to_string())
+        .spawn(move || {
+            for stream in listener.incoming() {
+                match stream {
+                    Ok(stream) => {
+                        let context = context.clone();
+                        if let Err(cause) = Builder::new()
+                            .name("control-client".to_string())
+                            .spawn(move || serve_client(&context, stream))
+                        {
+                            error!("Failed to spawn control client thread! Reason: {}", cause);
+                        }
+                    }
+                    Err(cause) => error!("Control socket failure! Reason: {}", cause),
+                }
+            }
+        })?;
+    Ok(())
+}
+
+
+/// handles requests of single client, until it disconnects
+#[instrument(skip(stream))]
+fn serve_client(context: &Context, stream: UnixStream) {
+    let mut writer = match stream.try_clone() {
+        Ok(writer) => writer,
+        Err(cause) => {
+            error!("Can't clone control stream! Reason: {}", cause);
+            return;
+        }
+    };
+    for line in BufReader::new(stream).lines() {
+        let line = match line {
+            Ok(line) if line.trim().is_empty() => continue,
+            Ok(line) => line,
+            Err(cause) => {
+                debug!("Control client disconnected. Reason: {}", cause);
+                return;
+            }
+        };
+        let response = match serde_json::from_str::<Request>(&line) {
+            Ok(request) => handle(context, &request),
+            Err(cause) => {
+                Response {
+                    ok: false,
+                    message: format!("Malformed request: {}", cause),
+                    ..Response::default()
+                }
+            }
+        };
+        let written = serde_json::to_string(&response)
+            .map_err(Error::other)
+            .and_then(|json| writer.write_all(format!("{}\n", json).as_bytes()));
+        if let Err(cause) = written {
+            debug!("Failed to write control response! Reason: {}", cause);
+            return;
+        }
+    }
+}
+
+
+/// performs control request
+#[instrument]
+pub fn handle(context: &Context, request: &Request) -> Response {
+    if request.command == Command::Status && request.service.is_none() {
+        let mut services: Vec<ServiceState> = context
+            .definitions
+            .get()
+            .iter()
+            .map(|service| context.state.get(&service.name()))
+            .collect();
+        services.sort_by(|a, b| a.name.cmp(&b.name));
+        return Response {
+            ok: true,
+            message: format!("{} services", services.len()),
+            services,
+            ..Response::default()
+        };
+    }
+    let service = match request.service.as_ref() {
+        // NOTE: running Deities knows only its last known-good definitions:
+        Some(name) => {
+            match context.definitions.find(name) {
+                Some(service) => service,
+                None => {
+                    return Response::from(Err(Mortal::ServiceNotFound {
+                        name: name.to_string(),
+                        services_dir: context.services_dir.clone(),
+                    }));
+                }
+            }
+        }
+        None => {
+            return Response {
+                ok: false,
+                message: "Service name required!".to_string(),
+                ..Response::default()
+            };
+        }
+    };
+    let name = service.name();
+
+    let response = match request.command {
+        Command::Status => {
+            Response {
+                ok: true,
+                message: service.to_string(),
+                ..Response::default()
+            }
+        }
+        Command::Check => {
+            let result = service.checks_for();
+            context
+                .state
+                .record_check(&name, &result, service.read_pid().ok());
+            Response::from(result)
+        }
+        Command::Start => {
+            context.state.set_paused(&name, false);
+            context.state.reset_restarts(&name);
+            start(context, &service)
+        }
+        Command::Stop => {
+            // NOTE: stopped service would be started again by next checks:
+            context.state.set_paused(&name, true);
+            let result = service.stop_service();
+            if result.is_ok() {
+                context.state.update(&name, |state| state.pid = None);
+            }
+            Response::from(result)
+        }
+        Command::Restart => {
+            if let Err(cause) = service.stop_service() {
+                warn!("Stop failed, starting anyway. Reason: {}", cause);
+            }
+            context.state.set_paused(&name, false);
+            context.state.reset_restarts(&name);
+            start(context, &service)
+        }
+        Command::Reload => Response::from(service.reload_service()),
+        Command::Pause => {
+            context.state.set_paused(&name, true);
+            Response {
+                ok: true,
+                message: format!("Paused monitoring of: {}", service),
+                ..Response::default()
+            }
+        }
+        Command::Unpause => {
+            context.state.set_paused(&name, false);
+            Response {
+                ok: true,
+                message: format!("Unpaused monitoring of: {}", service),
+                ..Response::default()
+            }
+        }
+        Command::Reset => {
+            context.state.reset_restarts(&name);
+            Response {
+                ok: true,
+                message: format!("Reset restarts of: {}", service),
+                ..Response::default()
+            }
+        }
+    };
+    Response {
+        services: vec![context.state.get(&name)],
+        ..response
+    }
+}
+
+
+/// starts service (once its dependencies are ready) and records its restart
+fn start(context: &Context, service: &Service) -> Response {
+    match context
+        .dependencies_ready(service)
+        .and_then(|_| service.start_service())
+    {
+        Ok(pid) => {
+            context.state.record_restart(&service.name(), pid);
+            Response {
+                ok: true,
+                message: format!("Started: {} with pid: {}", service, pid),
+                ..Response::default()
+            }
+        }
+        Err(failure) => Response::from(Err(failure)),
+    }
+}
+
+
+/// sends request to control socket of running Deities and returns its response
+#[instrument]
+pub fn request(control_socket: &str, request: &Request) -> Result<Response, Error> {
+    let mut stream = UnixStream::connect(control_socket)?;
+    let json = serde_json::to_string(request).map_err(Error::other)?;
+    stream.write_all(format!("{}\n", json).as_bytes())?;
+    let mut line = String::new();
+    BufReader::new(stream).read_line(&mut line)?;
+    serde_json::from_str(&line).map_err(Error::other)
+}
@@ -0,0 +1,145 @@
+use chrono::Local;
+use std::{
+    io::{prelude::*, Error},
+    sync::{Arc, Mutex},
+};
+
+use crate::{
+    mortal::{Blessing, Mortal},
+    *,
+};
+
+
+/// result of service checks
+pub static CHECK: &str = "check";
+
+/// failure of action performed on service
+pub static FAILURE: &str = "failure";
+
+/// (re)start of service performed by Deities
+pub static RESTART: &str = "restart";
+
+/// result of service start
+pub static START: &str = "start";
+
+/// result of service stop
+pub static STOP: &str = "stop";
+
+/// result of service reload
+pub static RELOAD: &str = "reload";
+
+/// exit of foreground service process
+pub static EXIT: &str = "exit";
+
+/// signal sent to service process by death watch
+pub static DEATH_WATCH: &str = "death_watch";
+
+/// result of notification sent with single notifier
+pub static NOTIFICATION: &str = "notification";
+
+
+lazy_static! {
+    /// event log file - events aren't recorded until it's opened
+    static ref EVENT_LOG: Mutex<Option<Arc<Mutex<RotatingFile>>>> = Mutex::new(None);
+}
+
+
+/// single line of event log
+#[derive(Serialize, Debug)]
+pub struct Entry<'a> {
+    /// RFC 3339 time of event
+    pub timestamp: String,
+
+    /// name of service
+    pub service: &'a str,
+
+    /// kind of event (like: CHECK)
+    pub kind: &'a str,
+
+    /// false if event is a failure
+    pub ok: bool,
+
+    /// outcome which describes event (if any)
+    pub mortal: Option<&'a dyn Outcome>,
+    pub pid: Option<i32>,
+    pub details: String,
+}
+
+
+/// starts recording events to given file - rotated at given size in KiB, keeping given
+/// amount of rotated files
+#[instrument]
+pub fn open(path: &str, max_size: u64, rotate_count: usize) -> Result<(), Error> {
+    let file = RotatingFile::shared(path, max_size * 1024, rotate_count)?;
+    let mut event_log = EVENT_LOG
+        .lock()
+        .map_err(|cause| Error::other(cause.to_string()))?;
+    *event_log = Some(file);
+    info!("Recording events to: {}", path);
+    Ok(())
+}
+
+
+/// writes event as single JSON line to event log (if it's open)
+pub fn record(
+    kind: &str,
+    service: &str,
+    pid: Option<i32>,
+    ok: bool,
+    mortal: Option<&dyn Outcome>,
+    details: &str,
+) {
+    let file = match EVENT_LOG.lock() {
+        Ok(event_log) => {
+            match event_log.as_ref() {
+                Some(file) => file.clone(),
+                None => return,
+            }
+        }
+        Err(cause) => {
+            error!("Event log lock poisoned! Reason: {}", cause);
+            return;
+        }
+    };
+    let entry = Entry {
+        timestamp: Local::now().to_rfc3339(),
+        service,
+        kind,
+        ok,
+        mortal,
+        pid,
+        details: details.to_string(),
+    };
+    let mut line = match serde_json::to_vec(&entry) {
+        Ok(line) => line,
+        Err(cause) => {
+            error!("Failed to serialize event: {:?}. Reason: {}", entry, cause);
+            return;
+        }
+    };
+    line.push(b'\n');
+    match file.lock() {
+        Ok(mut file) => {
+            if let Err(cause) = file.write_all(&line).and_then(|_| file.flush()) {
+                error!("Failed to write event! Reason: {}", cause);
+            }
+        }
+        Err(cause) => error!("Event log file lock poisoned! Reason: {}", cause),
+    };
+}
+
+
+/// writes outcome of action as event
+pub fn record_result(
+    kind: &str,
+    service: &str,
+    pid: Option<i32>,
+    result: &Result<Blessing, Mortal>,
+) {
+    match result {
+        Ok(ok) => record(kind, service, pid, true, Some(ok), &ok.to_string()),
+        Err(failure) => {
+            record(kind, service, pid, false, Some(failure), &failure.to_string())
+        }
+    }
+}
@@ -1,6 +1,9 @@
-use std::env;
+use std::{env, path::Path};
 
-use crate::{service::Service, *};
+use crate::{
+    service::{Service, UrlCheck},
+    *,
+};
 
 
 /// standard fields for service init file:
@@ -9,15 +12,44 @@ pub trait InitFields {
     fn disk_minimum_inodes(&self) -> i64;
     fn slack_webhook_url(&self) -> String;
     fn slack_alert_channel(&self) -> String;
+    fn alert_reminder_interval(&self) -> u64;
+    fn notifiers(&self) -> Vec<String>;
     fn checks_interval(&self) -> u64;
     fn checks_url_timeout(&self) -> u64;
+    fn checks_tcp_timeout(&self) -> u64;
+    fn checks_response_timeout(&self) -> u64;
     fn deathwatches_interval(&self) -> u64;
+    fn hook_timeout(&self) -> u64;
     fn user(&self) -> String;
     fn group(&self) -> String;
     fn work_dir(&self) -> String;
+    fn foreground(&self) -> bool;
+    fn stdout_log(&self) -> String;
+    fn stderr_log(&self) -> String;
+    fn log_max_size(&self) -> u64;
+    fn log_rotate_count(&self) -> usize;
+    fn log_tail_lines(&self) -> usize;
+    fn failure_threshold(&self) -> u64;
+    fn success_threshold(&self) -> u64;
+    fn max_restarts(&self) -> usize;
+    fn restarts_window(&self) -> u64;
+    fn restart_backoff_max(&self) -> u64;
     fn pid_file(&self) -> String;
     fn unix_socket(&self) -> String;
-    fn urls(&self) -> Vec<String>;
+    fn unix_socket_send(&self) -> String;
+    fn unix_socket_expect(&self) -> String;
+    fn tcp(&self) -> Vec<String>;
+    fn tcp_send(&self) -> String;
+    fn tcp_expect(&self) -> String;
+    fn urls(&self) -> Vec<UrlCheck>;
+    fn tls_expiry_warn_days(&self) -> u64;
+    fn ca_file(&self) -> String;
+    fn client_cert(&self) -> String;
+    fn client_key(&self) -> String;
+    fn resolve(&self) -> Vec<String>;
+    fn insecure(&self) -> bool;
+    fn requires(&self) -> Vec<String>;
+    fn after(&self) -> Vec<String>;
 }
 
 
@@ -83,6 +115,42 @@ impl InitFields for Service {
     }
 
 
+    /// names of notifiers to send notifications with (NOTIFIERS is comma separated)
+    #[instrument]
+    fn notifiers(&self) -> Vec<String> {
+        match self.notifiers.clone() {
+            Some(notifiers) => notifiers,
+            None => {
+                match env::var("NOTIFIERS") {
+                    Ok(notifiers) => {
+                        notifiers
+                            .split(',')
+                            .map(|notifier| notifier.trim().to_string())
+                            .filter(|notifier| !notifier.is_empty())
+                            .collect()
+                    }
+                    Err(_) => vec![],
+                }
+            }
+        }
+    }
+
+
+    /// pause before repeating the same alert - in ms (0 disables reminders)
+    #[instrument]
+    fn alert_reminder_interval(&self) -> u64 {
+        match self.alert_reminder_interval {
+            Some(alert_reminder_interval) => alert_reminder_interval,
+            None => {
+                match env::var("ALERT_REMINDER_INTERVAL") {
+                    Ok(interval) => interval.parse().unwrap_or(ALERT_REMINDER_INTERVAL),
+                    Err(_) => ALERT_REMINDER_INTERVAL,
+                }
+            }
+        }
+    }
+
+
     #[instrument]
     fn checks_interval(&self) -> u64 {
         match self.checks_interval {
@@ -121,6 +189,50 @@ impl InitFields for Service {
     }
 
 
+    #[instrument]
+    fn checks_tcp_timeout(&self) -> u64 {
+        // NOTE: connect timeout has to be non zero:
+        match self.checks_tcp_timeout {
+            Some(checks_tcp_timeout) => checks_tcp_timeout.max(1),
+            None => {
+                match env::var("CHECKS_TCP_TIMEOUT") {
+                    Ok(timeout) => timeout.parse().unwrap_or(CHECKS_TCP_TIMEOUT).max(1),
+                    Err(_) => CHECKS_TCP_TIMEOUT,
+                }
+            }
+        }
+    }
+
+
+    #[instrument]
+    fn checks_response_timeout(&self) -> u64 {
+        // NOTE: read timeout has to be non zero:
+        match self.checks_response_timeout {
+            Some(checks_response_timeout) => checks_response_timeout.max(1),
+            None => {
+                match env::var("CHECKS_RESPONSE_TIMEOUT") {
+                    Ok(timeout) => timeout.parse().unwrap_or(CHECKS_RESPONSE_TIMEOUT).max(1),
+                    Err(_) => CHECKS_RESPONSE_TIMEOUT,
+                }
+            }
+        }
+    }
+
+
+    #[instrument]
+    fn hook_timeout(&self) -> u64 {
+        match self.hook_timeout {
+            Some(hook_timeout) => hook_timeout,
+            None => {
+                match env::var("HOOK_TIMEOUT") {
+                    Ok(timeout) => timeout.parse().unwrap_or(HOOK_TIMEOUT),
+                    Err(_) => HOOK_TIMEOUT,
+                }
+            }
+        }
+    }
+
+
     #[instrument]
     fn deathwatches_interval(&self) -> u64 {
         match self.deathwatches_interval {
@@ -181,11 +293,161 @@ impl InitFields for Service {
     }
 
 
+    /// returns path to file with service standard output
+    #[instrument]
+    fn stdout_log(&self) -> String {
+        match self.stdout_log.clone() {
+            Some(path) => path,
+            None => self.log_file.clone().unwrap_or_default(),
+        }
+    }
+
+
+    /// returns path to file with service standard error
+    #[instrument]
+    fn stderr_log(&self) -> String {
+        match self.stderr_log.clone() {
+            Some(path) => path,
+            None => self.log_file.clone().unwrap_or_default(),
+        }
+    }
+
+
+    /// maximum size of service log file - in KiB
+    #[instrument]
+    fn log_max_size(&self) -> u64 {
+        match self.log_max_size {
+            Some(log_max_size) => log_max_size,
+            None => {
+                match env::var("LOG_MAX_SIZE") {
+                    Ok(log_max_size) => log_max_size.parse().unwrap_or(LOG_MAX_SIZE),
+                    Err(_) => LOG_MAX_SIZE,
+                }
+            }
+        }
+    }
+
+
+    #[instrument]
+    fn log_rotate_count(&self) -> usize {
+        match self.log_rotate_count {
+            Some(log_rotate_count) => log_rotate_count,
+            None => {
+                match env::var("LOG_ROTATE_COUNT") {
+                    Ok(count) => count.parse().unwrap_or(LOG_ROTATE_COUNT),
+                    Err(_) => LOG_ROTATE_COUNT,
+                }
+            }
+        }
+    }
+
+
+    #[instrument]
+    fn log_tail_lines(&self) -> usize {
+        match self.log_tail_lines {
+            Some(log_tail_lines) => log_tail_lines,
+            None => {
+                match env::var("LOG_TAIL_LINES") {
+                    Ok(lines) => lines.parse().unwrap_or(LOG_TAIL_LINES),
+                    Err(_) => LOG_TAIL_LINES,
+                }
+            }
+        }
+    }
+
+
+    /// amount of failures of the same check in a row, before service is considered down
+    #[instrument]
+    fn failure_threshold(&self) -> u64 {
+        match self.failure_threshold {
+            Some(failure_threshold) => failure_threshold.max(1),
+            None => {
+                match env::var("FAILURE_THRESHOLD") {
+                    Ok(threshold) => threshold.parse().unwrap_or(FAILURE_THRESHOLD).max(1),
+                    Err(_) => FAILURE_THRESHOLD,
+                }
+            }
+        }
+    }
+
+
+    /// amount of passed checks in a row, before service is considered recovered
+    #[instrument]
+    fn success_threshold(&self) -> u64 {
+        match self.success_threshold {
+            Some(success_threshold) => success_threshold.max(1),
+            None => {
+                match env::var("SUCCESS_THRESHOLD") {
+                    Ok(threshold) => threshold.parse().unwrap_or(SUCCESS_THRESHOLD).max(1),
+                    Err(_) => SUCCESS_THRESHOLD,
+                }
+            }
+        }
+    }
+
+
+    #[instrument]
+    fn max_restarts(&self) -> usize {
+        match self.max_restarts {
+            Some(max_restarts) => max_restarts,
+            None => {
+                match env::var("MAX_RESTARTS") {
+                    Ok(max_restarts) => max_restarts.parse().unwrap_or(MAX_RESTARTS),
+                    Err(_) => MAX_RESTARTS,
+                }
+            }
+        }
+    }
+
+
+    /// time window of counted automatic restarts - in ms
+    #[instrument]
+    fn restarts_window(&self) -> u64 {
+        match self.restarts_window {
+            Some(restarts_window) => restarts_window,
+            None => {
+                match env::var("RESTARTS_WINDOW") {
+                    Ok(window) => window.parse().unwrap_or(RESTARTS_WINDOW),
+                    Err(_) => RESTARTS_WINDOW,
+                }
+            }
+        }
+    }
+
+
+    /// maximum pause between automatic restarts - in ms
+    #[instrument]
+    fn restart_backoff_max(&self) -> u64 {
+        match self.restart_backoff_max {
+            Some(restart_backoff_max) => restart_backoff_max,
+            None => {
+                match env::var("RESTART_BACKOFF_MAX") {
+                    Ok(backoff) => backoff.parse().unwrap_or(RESTART_BACKOFF_MAX),
+                    Err(_) => RESTART_BACKOFF_MAX,
+                }
+            }
+        }
+    }
+
+
+    /// returns true if service is supervised in foreground
+    #[instrument]
+    fn foreground(&self) -> bool {
+        self.foreground.unwrap_or(false)
+    }
+
+
     /// returns service pid file to monitor
+    /// (foreground services have their pid file written by Veles)
     #[instrument]
     fn pid_file(&self) -> String {
         match self.pid_file.clone() {
             Some(path) => path,
+            None if self.foreground() => {
+                self.services_dir()
+                    .map(|services_dir| format!("{}/.{}.pid", services_dir, self.name()))
+                    .unwrap_or_default()
+            }
             None => "".to_string(),
         }
     }
@@ -201,12 +463,126 @@ impl InitFields for Service {
     }
 
 
+    /// returns message to send through unix socket
+    #[instrument]
+    fn unix_socket_send(&self) -> String {
+        match self.unix_socket_send.clone() {
+            Some(message) => message,
+            None => UNIX_SOCKET_MSG.to_string(),
+        }
+    }
+
+
+    /// returns expected response from unix socket (empty if response is ignored)
+    #[instrument]
+    fn unix_socket_expect(&self) -> String {
+        self.unix_socket_expect.clone().unwrap_or_default()
+    }
+
+
+    /// returns TCP addresses to check
+    #[instrument]
+    fn tcp(&self) -> Vec<String> {
+        self.tcp.clone().unwrap_or_default()
+    }
+
+
+    /// returns message to send to TCP addresses (empty if nothing is sent)
+    #[instrument]
+    fn tcp_send(&self) -> String {
+        self.tcp_send.clone().unwrap_or_default()
+    }
+
+
+    /// returns expected response from TCP addresses (empty if response is ignored)
+    #[instrument]
+    fn tcp_expect(&self) -> String {
+        self.tcp_expect.clone().unwrap_or_default()
+    }
+
+
     /// returns urls list to check
     #[instrument]
-    fn urls(&self) -> Vec<String> {
-        match self.urls.clone() {
-            Some(vector) => vector,
-            None => vec![],
+    fn urls(&self) -> Vec<UrlCheck> {
+        self.urls.clone().unwrap_or_default()
+    }
+
+
+    /// returns amount of days before TLS certificate expiry to warn about (0 - disabled)
+    #[instrument]
+    fn tls_expiry_warn_days(&self) -> u64 {
+        match self.tls_expiry_warn_days {
+            Some(tls_expiry_warn_days) => tls_expiry_warn_days,
+            None => {
+                match env::var("TLS_EXPIRY_WARN_DAYS") {
+                    Ok(days) => days.parse().unwrap_or(TLS_EXPIRY_WARN_DAYS),
+                    Err(_) => TLS_EXPIRY_WARN_DAYS,
+                }
+            }
         }
     }
+
+
+    /// returns CA certificates file to verify TLS peers with.
+    /// Empty if none of system ones exists (curl default is used then)
+    #[instrument]
+    fn ca_file(&self) -> String {
+        match self.ca_file.clone() {
+            Some(ca_file) => ca_file,
+            None => {
+                match env::var("CA_FILE") {
+                    Ok(ca_file) => ca_file,
+                    Err(_) => {
+                        CACERT_PEMS
+                            .iter()
+                            .find(|path| Path::new(path).exists())
+                            .map(|path| path.to_string())
+                            .unwrap_or_default()
+                    }
+                }
+            }
+        }
+    }
+
+
+    /// returns path to client certificate (empty if unset)
+    #[instrument]
+    fn client_cert(&self) -> String {
+        self.client_cert.clone().unwrap_or_default()
+    }
+
+
+    /// returns path to private key of client certificate (empty if unset)
+    #[instrument]
+    fn client_key(&self) -> String {
+        self.client_key.clone().unwrap_or_default()
+    }
+
+
+    /// returns host resolve overrides in curl format: HOST:PORT:ADDRESS
+    #[instrument]
+    fn resolve(&self) -> Vec<String> {
+        self.resolve.clone().unwrap_or_default()
+    }
+
+
+    /// true if TLS peers shouldn't be verified
+    #[instrument]
+    fn insecure(&self) -> bool {
+        self.insecure.unwrap_or(false)
+    }
+
+
+    /// returns names of services required by service
+    #[instrument]
+    fn requires(&self) -> Vec<String> {
+        self.requires.clone().unwrap_or_default()
+    }
+
+
+    /// returns names of services to start before service
+    #[instrument]
+    fn after(&self) -> Vec<String> {
+        self.after.clone().unwrap_or_default()
+    }
 }
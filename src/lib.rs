@@ -6,18 +6,31 @@ extern crate lazy_static;
 
 
 pub mod common;
+pub mod context;
+pub mod control;
+pub mod event_log;
 pub mod init_fields;
+pub mod metrics;
 pub mod mortal;
+pub mod notifier;
 pub mod perun;
+pub mod rotating_file;
+pub mod scheduler;
 pub mod service;
+pub mod state;
 pub mod svarog;
 pub mod veles;
 
 
 pub use common::*;
+pub use context::Context;
 pub use init_fields::InitFields;
+pub use mortal::Outcome;
 pub use perun::Perun;
+pub use rotating_file::RotatingFile;
+pub use scheduler::Scheduler;
 pub use service::Service;
+pub use state::State;
 pub use svarog::Svarog;
 pub use tracing::{debug, error, info, instrument, trace, warn};
 pub use veles::Veles;
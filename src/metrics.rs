@@ -0,0 +1,285 @@
+use std::{
+    collections::BTreeMap,
+    io::{prelude::*, BufReader, Error},
+    net::{TcpListener, TcpStream},
+    sync::Mutex,
+    thread::Builder,
+    time::Duration,
+};
+
+use crate::*;
+
+
+/// last checks outcome of service (1 - passed, 0 - failed)
+pub static SERVICE_UP: &str = "deities_service_up";
+
+/// amount of check results, start results and failures - by kind and variant of outcome
+pub static OUTCOME_TOTAL: &str = "deities_outcome_total";
+
+/// amount of service (re)starts performed by Deities
+pub static RESTARTS_TOTAL: &str = "deities_service_restarts_total";
+
+/// duration of service checks
+pub static CHECK_DURATION: &str = "deities_check_duration_seconds";
+
+/// free disk space seen by disk check
+pub static DISK_FREE_BYTES: &str = "deities_disk_free_bytes";
+
+/// free disk inodes seen by disk check
+pub static DISK_FREE_INODES: &str = "deities_disk_free_inodes";
+
+/// days left until expiry of TLS certificate seen by URL check
+pub static TLS_EXPIRY_DAYS: &str = "deities_tls_expiry_days";
+
+
+/// how long metrics endpoint waits for client to send request or receive response
+static CLIENT_TIMEOUT: Duration = Duration::from_millis(3000);
+
+
+/// upper bounds of check duration histogram buckets - in seconds
+static BUCKETS: [f64; 11] = [0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0];
+
+
+lazy_static! {
+    static ref METRICS: Metrics = Metrics::default();
+}
+
+
+/// observations of single histogram series
+#[derive(Debug, Clone, Default)]
+struct Histogram {
+    /// amount of observations per bucket (not cumulative)
+    buckets: [u64; 11],
+    count: u64,
+    sum: f64,
+}
+
+
+/// all metric series - by metric name and rendered labels
+#[derive(Debug, Default)]
+struct Metrics {
+    gauges: Mutex<BTreeMap<(String, String), f64>>,
+    counters: Mutex<BTreeMap<(String, String), u64>>,
+    histograms: Mutex<BTreeMap<(String, String), Histogram>>,
+}
+
+
+/// returns metric type and help text
+fn describe(name: &str) -> (&'static str, &'static str) {
+    match name {
+        n if n == SERVICE_UP => ("gauge", "Last checks outcome of service (1 - passed)"),
+        n if n == OUTCOME_TOTAL => ("counter", "Outcomes of checks and starts by kind"),
+        n if n == RESTARTS_TOTAL => ("counter", "Service (re)starts performed by Deities"),
+        n if n == CHECK_DURATION => ("histogram", "Duration of service checks"),
+        n if n == DISK_FREE_BYTES => ("gauge", "Free disk space seen by disk check"),
+        n if n == DISK_FREE_INODES => ("gauge", "Free disk inodes seen by disk check"),
+        n if n == TLS_EXPIRY_DAYS => ("gauge", "Days left until TLS certificate expiry"),
+        _ => ("untyped", ""),
+    }
+}
+
+
+/// renders labels in Prometheus text format
+fn labels(labels: &[(&str, &str)]) -> String {
+    labels
+        .iter()
+        .map(|(name, value)| {
+            format!(
+                "{}=\"{}\"",
+                name,
+                value
+                    .replace('\\', "\\\\")
+                    .replace('"', "\\\"")
+                    .replace('\n', "\\n")
+            )
+        })
+        .collect::<Vec<String>>()
+        .join(",")
+}
+
+
+/// sets gauge value
+pub fn gauge(name: &str, series: &[(&str, &str)], value: f64) {
+    if let Ok(mut gauges) = METRICS.gauges.lock() {
+        gauges.insert((name.to_string(), labels(series)), value);
+    }
+}
+
+
+/// increments counter
+pub fn increment(name: &str, series: &[(&str, &str)]) {
+    if let Ok(mut counters) = METRICS.counters.lock() {
+        *counters.entry((name.to_string(), labels(series))).or_insert(0) += 1;
+    }
+}
+
+
+/// adds observation (in seconds) to histogram
+pub fn observe(name: &str, series: &[(&str, &str)], seconds: f64) {
+    if let Ok(mut histograms) = METRICS.histograms.lock() {
+        let histogram = histograms
+            .entry((name.to_string(), labels(series)))
+            .or_default();
+        if let Some(bucket) = BUCKETS.iter().position(|bound| seconds <= *bound) {
+            histogram.buckets[bucket] += 1;
+        }
+        histogram.count += 1;
+        histogram.sum += seconds;
+    }
+}
+
+
+/// returns metric name with its labels - without braces if there are none
+fn labeled(name: &str, series: &str) -> String {
+    match series.is_empty() {
+        true => name.to_string(),
+        false => format!("{}{{{}}}", name, series),
+    }
+}
+
+
+/// renders all metrics in Prometheus text format
+pub fn render() -> String {
+    let mut lines = vec![];
+    let mut described = String::new();
+    let mut header = |lines: &mut Vec<String>, name: &str| {
+        if described != name {
+            let (kind, help) = describe(name);
+            lines.push(format!("# HELP {} {}", name, help));
+            lines.push(format!("# TYPE {} {}", name, kind));
+            described = name.to_string();
+        }
+    };
+    if let Ok(gauges) = METRICS.gauges.lock() {
+        for ((name, series), value) in gauges.iter() {
+            header(&mut lines, name);
+            lines.push(format!("{} {}", labeled(name, series), value));
+        }
+    }
+    if let Ok(counters) = METRICS.counters.lock() {
+        for ((name, series), value) in counters.iter() {
+            header(&mut lines, name);
+            lines.push(format!("{} {}", labeled(name, series), value));
+        }
+    }
+    if let Ok(histograms) = METRICS.histograms.lock() {
+        for ((name, series), histogram) in histograms.iter() {
+            header(&mut lines, name);
+            let mut cumulative = 0;
+            let bucket = format!("{}_bucket", name);
+            let bucket_series = |bound: &str| {
+                match series.is_empty() {
+                    true => format!("le=\"{}\"", bound),
+                    false => format!("{},le=\"{}\"", series, bound),
+                }
+            };
+            for (bound, amount) in BUCKETS.iter().zip(histogram.buckets.iter()) {
+                cumulative += amount;
+                let series = bucket_series(&bound.to_string());
+                lines.push(format!("{} {}", labeled(&bucket, &series), cumulative));
+            }
+            let series_inf = bucket_series("+Inf");
+            lines.push(format!("{} {}", labeled(&bucket, &series_inf), histogram.count));
+            let sum = format!("{}_sum", name);
+            lines.push(format!("{} {}", labeled(&sum, series), histogram.sum));
+            let count = format!("{}_count", name);
+            lines.push(format!("{} {}", labeled(&count, series), histogram.count));
+        }
+    }
+    lines.push("".to_string());
+    lines.join("\n")
+}
+
+
+/// starts HTTP server thread exposing metrics under /metrics
+#[instrument]
+pub fn serve(address: &str) -> Result<(), Error> {
+    let listener = TcpListener::bind(address)?;
+    info!("Metrics available on: http://{}/metrics", address);
+    Builder::new()
+        .name("metrics".to_string())
+        .spawn(move || {
+            for stream in listener.incoming() {
+                match stream {
+                    Ok(stream) => {
+                        if let Err(cause) = respond(stream) {
+                            debug!("Failed to serve metrics! Reason: {}", cause);
+                        }
+                    }
+                    Err(cause) => error!("Metrics listener failure! Reason: {}", cause),
+                }
+            }
+        })?;
+    Ok(())
+}
+
+
+/// responds to single HTTP request
+fn respond(mut stream: TcpStream) -> Result<(), Error> {
+    // NOTE: requests are served one by one - idle client can't block endpoint forever:
+    stream.set_read_timeout(Some(CLIENT_TIMEOUT))?;
+    stream.set_write_timeout(Some(CLIENT_TIMEOUT))?;
+    let mut request_line = String::new();
+    BufReader::new(stream.try_clone()?).read_line(&mut request_line)?;
+    let (status, content_type, body) = match request_line.split_whitespace().nth(1) {
+        Some("/metrics") => ("200 OK", "text/plain; version=0.0.4", render()),
+        _ => ("404 Not Found", "text/plain", "Not found\n".to_string()),
+    };
+    stream.write_all(
+        format!(
+            "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: \
+             close\r\n\r\n{}",
+            status,
+            content_type,
+            body.len(),
+            body
+        )
+        .as_bytes(),
+    )
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+
+    #[test]
+    fn render_uses_prometheus_text_format() {
+        gauge(SERVICE_UP, &[("service", "render \"test\"")], 1.0);
+        increment(RESTARTS_TOTAL, &[("service", "render")]);
+        increment(RESTARTS_TOTAL, &[("service", "render")]);
+        observe(CHECK_DURATION, &[("service", "render")], 0.02);
+        observe(CHECK_DURATION, &[("service", "render")], 20.0);
+        observe(CHECK_DURATION, &[], 0.02);
+        let rendered = render();
+        let lines: Vec<&str> = rendered.lines().collect();
+        for expected in [
+            "# HELP deities_service_up Last checks outcome of service (1 - passed)",
+            "# TYPE deities_service_up gauge",
+            "deities_service_up{service=\"render \\\"test\\\"\"} 1",
+            "# TYPE deities_service_restarts_total counter",
+            "deities_service_restarts_total{service=\"render\"} 2",
+            "# TYPE deities_check_duration_seconds histogram",
+            "deities_check_duration_seconds_bucket{service=\"render\",le=\"0.01\"} 0",
+            "deities_check_duration_seconds_bucket{service=\"render\",le=\"0.025\"} 1",
+            "deities_check_duration_seconds_bucket{service=\"render\",le=\"10\"} 1",
+            "deities_check_duration_seconds_bucket{service=\"render\",le=\"+Inf\"} 2",
+            "deities_check_duration_seconds_sum{service=\"render\"} 20.02",
+            "deities_check_duration_seconds_count{service=\"render\"} 2",
+            "deities_check_duration_seconds_bucket{le=\"0.025\"} 1",
+            "deities_check_duration_seconds_sum 0.02",
+            "deities_check_duration_seconds_count 1",
+        ] {
+            assert!(lines.contains(&expected), "Missing: {}", expected);
+        }
+        assert!(!rendered.contains("{}"));
+        assert_eq!(
+            lines
+                .iter()
+                .filter(|line| **line == "# TYPE deities_service_up gauge")
+                .count(),
+            1
+        );
+    }
+}
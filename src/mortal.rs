@@ -1,48 +1,397 @@
 use curl::Error as CurlError;
+use serde::{ser::SerializeMap, Serialize, Serializer};
+use serde_json::{json, Value};
 use slack_hook::Error as SlackError;
 use std::{
+    error::Error as StdError,
     fmt::{self, Display},
     io::Error,
 };
+use toml::de::Error as TomlError;
 
-use crate::{init_fields::InitFields, service::Service};
+use crate::{init_fields::InitFields, service::Service, NAME};
 
 
+/// kind of Mortal - determined by hundreds of its code
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum MortalKind {
+    Ok,
+    CheckFailure,
+    ConfigFailure,
+    ServiceFailure,
+    NotificationFailure,
+    ControlFailure,
+    SanityFailure,
+}
+
+
+impl MortalKind {
+    /// returns name of kind - as serialized
+    pub fn name(&self) -> &'static str {
+        match self {
+            MortalKind::Ok => "ok",
+            MortalKind::CheckFailure => "check_failure",
+            MortalKind::ConfigFailure => "config_failure",
+            MortalKind::ServiceFailure => "service_failure",
+            MortalKind::NotificationFailure => "notification_failure",
+            MortalKind::ControlFailure => "control_failure",
+            MortalKind::SanityFailure => "sanity_failure",
+        }
+    }
+}
+
+
+/// structured form of IO error
+fn io_cause(cause: &Error) -> Value {
+    json!({
+        "type": "io",
+        "kind": format!("{:?}", cause.kind()),
+        "os_code": cause.raw_os_error(),
+        "message": cause.to_string(),
+    })
+}
+
+
+/// structured form of curl error
+fn curl_cause(cause: &CurlError) -> Value {
+    json!({
+        "type": "curl",
+        "code": cause.code(),
+        "message": cause.description(),
+        "extra": cause.extra_description(),
+    })
+}
+
+
+// Outcome is the common interface of Blessing and Mortal - results of actions
+// performed by Deities on services. Both serialize to the same structured form,
+// so successes and failures can be told apart by their code and kind.
+//
+
+pub trait Outcome: Display + fmt::Debug {
+    /// returns stable code of outcome. Hundreds determine its kind
+    fn code(&self) -> u16;
+
+    /// returns name of outcome variant
+    fn variant(&self) -> &'static str;
+
+    /// returns service which outcome is about (if any)
+    fn service(&self) -> Option<&Service>;
+
+    /// returns type of service check which produced this outcome (if any)
+    fn check(&self) -> Option<&'static str>;
+
+    /// returns fields specific to outcome variant - other than its service and cause
+    fn fields(&self) -> Value;
+
+
+    /// returns structured cause of outcome (if any)
+    fn cause(&self) -> Option<Value> {
+        None
+    }
+
+
+    /// returns kind of outcome
+    fn kind(&self) -> MortalKind {
+        match self.code() / 100 {
+            1 => MortalKind::Ok,
+            2 => MortalKind::CheckFailure,
+            3 => MortalKind::ConfigFailure,
+            4 => MortalKind::ServiceFailure,
+            5 => MortalKind::NotificationFailure,
+            6 => MortalKind::ControlFailure,
+            _ => MortalKind::SanityFailure,
+        }
+    }
+
+
+    /// true if outcome is a success
+    fn is_ok(&self) -> bool {
+        self.kind() == MortalKind::Ok
+    }
+}
+
+
+/// successful outcome of action performed on service
 #[derive(Debug)]
-pub enum Mortal {
-    /// Successes:
+pub enum Blessing {
     OkAllChecks {
-        service: Service,
+        service: Box<Service>,
         amount: i32,
     },
     OkUrlsChecks {
-        service: Service,
+        service: Box<Service>,
     },
     OkUnixSockCheck {
-        service: Service,
+        service: Box<Service>,
     },
     OkPidAlive {
-        service: Service,
+        service: Box<Service>,
         pid: i32,
     },
     OkPidInterrupted {
-        service: Service,
+        service: Box<Service>,
         pid: i32,
     },
     OkPidAlreadyInterrupted {
-        service: Service,
+        service: Box<Service>,
         pid: i32,
     },
     OkDiskCheck {
-        service: Service,
+        service: Box<Service>,
+    },
+    OkServiceStopped {
+        service: Box<Service>,
     },
+    OkServiceReloaded {
+        service: Box<Service>,
+    },
+    OkTcpChecks {
+        service: Box<Service>,
+    },
+}
+
+
+impl Outcome for Blessing {
+    fn code(&self) -> u16 {
+        match self {
+            Blessing::OkAllChecks {
+                ..
+            } => 100,
+            Blessing::OkUrlsChecks {
+                ..
+            } => 101,
+            Blessing::OkUnixSockCheck {
+                ..
+            } => 102,
+            Blessing::OkPidAlive {
+                ..
+            } => 103,
+            Blessing::OkPidInterrupted {
+                ..
+            } => 104,
+            Blessing::OkPidAlreadyInterrupted {
+                ..
+            } => 105,
+            Blessing::OkDiskCheck {
+                ..
+            } => 106,
+            Blessing::OkServiceStopped {
+                ..
+            } => 107,
+            Blessing::OkServiceReloaded {
+                ..
+            } => 108,
+            Blessing::OkTcpChecks {
+                ..
+            } => 109,
+        }
+    }
+
+
+    fn variant(&self) -> &'static str {
+        match self {
+            Blessing::OkAllChecks {
+                ..
+            } => "OkAllChecks",
+            Blessing::OkUrlsChecks {
+                ..
+            } => "OkUrlsChecks",
+            Blessing::OkUnixSockCheck {
+                ..
+            } => "OkUnixSockCheck",
+            Blessing::OkPidAlive {
+                ..
+            } => "OkPidAlive",
+            Blessing::OkPidInterrupted {
+                ..
+            } => "OkPidInterrupted",
+            Blessing::OkPidAlreadyInterrupted {
+                ..
+            } => "OkPidAlreadyInterrupted",
+            Blessing::OkDiskCheck {
+                ..
+            } => "OkDiskCheck",
+            Blessing::OkServiceStopped {
+                ..
+            } => "OkServiceStopped",
+            Blessing::OkServiceReloaded {
+                ..
+            } => "OkServiceReloaded",
+            Blessing::OkTcpChecks {
+                ..
+            } => "OkTcpChecks",
+        }
+    }
+
+
+    fn service(&self) -> Option<&Service> {
+        match self {
+            Blessing::OkAllChecks {
+                service,
+                ..
+            }
+            | Blessing::OkUrlsChecks {
+                service,
+            }
+            | Blessing::OkUnixSockCheck {
+                service,
+            }
+            | Blessing::OkPidAlive {
+                service,
+                ..
+            }
+            | Blessing::OkPidInterrupted {
+                service,
+                ..
+            }
+            | Blessing::OkPidAlreadyInterrupted {
+                service,
+                ..
+            }
+            | Blessing::OkDiskCheck {
+                service,
+            }
+            | Blessing::OkServiceStopped {
+                service,
+            }
+            | Blessing::OkServiceReloaded {
+                service,
+            }
+            | Blessing::OkTcpChecks {
+                service,
+            } => Some(service),
+        }
+    }
+
+
+    fn check(&self) -> Option<&'static str> {
+        match self {
+            Blessing::OkDiskCheck {
+                ..
+            } => Some("disk"),
+            Blessing::OkUnixSockCheck {
+                ..
+            } => Some("unix_socket"),
+            Blessing::OkPidAlive {
+                ..
+            } => Some("pid"),
+            Blessing::OkUrlsChecks {
+                ..
+            } => Some("urls"),
+            Blessing::OkTcpChecks {
+                ..
+            } => Some("tcp"),
+            _ => None,
+        }
+    }
+
+
+    fn fields(&self) -> Value {
+        match self {
+            Blessing::OkAllChecks {
+                amount,
+                ..
+            } => json!({ "amount": amount }),
+            Blessing::OkPidAlive {
+                pid,
+                ..
+            }
+            | Blessing::OkPidInterrupted {
+                pid,
+                ..
+            }
+            | Blessing::OkPidAlreadyInterrupted {
+                pid,
+                ..
+            } => json!({ "pid": pid }),
+            _ => json!({}),
+        }
+    }
+}
+
+
+impl Display for Blessing {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Blessing: {}",
+            match *self {
+                Blessing::OkAllChecks {
+                    ref service,
+                    ref amount,
+                } => {
+                    format!(
+                        "Ok: {} successfully passed all: {} checks!",
+                        service, amount
+                    )
+                }
+                Blessing::OkUrlsChecks {
+                    ref service,
+                } => {
+                    format!("Ok: {} successfully passed URLs checks!", service)
+                }
+                Blessing::OkUnixSockCheck {
+                    ref service,
+                } => {
+                    format!("Ok: {} successfully passed UNIX sock checks!", service)
+                }
+                Blessing::OkPidAlive {
+                    ref service,
+                    ref pid,
+                } => {
+                    format!("Ok: Alive pid: {} of service: {}", pid, service)
+                }
+                Blessing::OkPidInterrupted {
+                    ref service,
+                    ref pid,
+                } => {
+                    format!("Ok: Interrupted pid: {} of service: {}", pid, service)
+                }
+                Blessing::OkPidAlreadyInterrupted {
+                    ref service,
+                    ref pid,
+                } => {
+                    format!(
+                        "Ok: Already interrupted pid: {} of service: {}",
+                        pid, service
+                    )
+                }
+                Blessing::OkDiskCheck {
+                    ref service,
+                } => {
+                    format!("Ok: Disk check passed for service: {}", service)
+                }
+                Blessing::OkServiceStopped {
+                    ref service,
+                } => {
+                    format!("Ok: Stopped service: {}", service)
+                }
+                Blessing::OkServiceReloaded {
+                    ref service,
+                } => {
+                    format!("Ok: Reloaded service: {}", service)
+                }
+                Blessing::OkTcpChecks {
+                    ref service,
+                } => {
+                    format!("Ok: {} successfully passed TCP checks!", service)
+                }
+            }
+        )
+    }
+}
+
 
-    /// Failures:
+/// failure of action performed on service
+#[derive(Debug)]
+pub enum Mortal {
     CheckNoServiceChecks {
-        service: Service,
+        service: Box<Service>,
     },
     CheckPidDead {
-        service: Service,
+        service: Box<Service>,
         pid: i32,
     },
 
@@ -56,59 +405,164 @@ pub enum Mortal {
     },
     DefinitionDecodeFailure {
         ini_name: String,
-        cause: Error,
+        line: Option<usize>,
+        column: Option<usize>,
+        key: Option<String>,
+        snippet: String,
+        cause: TomlError,
     },
     DefinitionLoadFailure {
         ini_name: String,
         cause: Error,
     },
+    DefinitionUnknownKeys {
+        ini_name: String,
+        keys: Vec<(String, Option<String>)>,
+    },
+    DefinitionInvalid {
+        ini_name: String,
+        key: String,
+        reason: String,
+    },
 
     CheckURL {
-        service: Service,
+        service: Box<Service>,
         url: String,
         cause: CurlError,
     },
     CheckURLFail {
-        service: Service,
+        service: Box<Service>,
         cause: CurlError,
     },
     CheckPidfileMalformed {
-        service: Service,
+        service: Box<Service>,
     },
     CheckPidfileUnaccessible {
-        service: Service,
+        service: Box<Service>,
         cause: Error,
     },
     CheckUnixSocket {
-        service: Service,
+        service: Box<Service>,
         cause: Error,
     },
     CheckUnixSocketMissing {
-        service: Service,
+        service: Box<Service>,
         cause: Error,
     },
     CheckDiskSpace {
-        service: Service,
+        service: Box<Service>,
     },
     CheckDiskInodes {
-        service: Service,
+        service: Box<Service>,
+    },
+    CheckTcp {
+        service: Box<Service>,
+        address: String,
+        cause: Error,
+    },
+    CheckUnexpectedResponse {
+        service: Box<Service>,
+        check: &'static str,
+        target: String,
+        expected: String,
+        response: String,
+    },
+    CheckURLAssertion {
+        service: Box<Service>,
+        url: String,
+        status: u32,
+        latency: u64,
+        reason: String,
+    },
+    CheckTlsExpiry {
+        service: Box<Service>,
+        url: String,
+        days: i64,
+        expires: String,
     },
 
+    ServiceNotFound {
+        name: String,
+        services_dir: String,
+    },
     ServiceNoStartDefined {
-        service: Service,
+        service: Box<Service>,
     },
     ServiceStartFailure {
-        service: Service,
+        service: Box<Service>,
+        cause: Error,
+        output: Vec<String>,
+    },
+    ServiceNoReloadDefined {
+        service: Box<Service>,
+    },
+    ServiceValidateFailure {
+        service: Box<Service>,
+        cause: Error,
+    },
+    ServiceConfigureFailure {
+        service: Box<Service>,
+        cause: Error,
+    },
+    ServiceAfterStartFailure {
+        service: Box<Service>,
+        cause: Error,
+    },
+    ServiceStopFailure {
+        service: Box<Service>,
+        cause: Error,
+    },
+    ServiceAfterStopFailure {
+        service: Box<Service>,
         cause: Error,
     },
+    ServiceReloadFailure {
+        service: Box<Service>,
+        cause: Error,
+    },
+    ServiceWaitingForDependency {
+        service: Box<Service>,
+        dependency: String,
+        reason: String,
+    },
+    ServiceRestartBackedOff {
+        service: Box<Service>,
+        remaining: u64,
+    },
+    ServiceGivenUp {
+        service: Box<Service>,
+        restarts: usize,
+    },
+    ServiceForegroundUnsupervised {
+        service: Box<Service>,
+    },
 
     NotificationConfigFailure {
-        service: Service,
+        service: Box<Service>,
         cause: Error,
     },
     NotificationFailure {
         cause: SlackError,
     },
+    NotifierFailure {
+        notifier: String,
+        cause: String,
+    },
+    NotifierUnknown {
+        service: Box<Service>,
+        notifier: String,
+    },
+    NotifiersDecodeFailure {
+        file_name: String,
+        cause: TomlError,
+    },
+
+    ControlSocketUnavailable {
+        control_socket: String,
+    },
+    ControlRequestFailure {
+        message: String,
+    },
 
     SanityCheckFailure {
         message: String,
@@ -116,57 +570,811 @@ pub enum Mortal {
 }
 
 
+impl Outcome for Mortal {
+    fn check(&self) -> Option<&'static str> {
+        match self {
+            Mortal::CheckDiskSpace {
+                ..
+            }
+            | Mortal::CheckDiskInodes {
+                ..
+            } => Some("disk"),
+            Mortal::CheckUnixSocket {
+                ..
+            }
+            | Mortal::CheckUnixSocketMissing {
+                ..
+            } => Some("unix_socket"),
+            Mortal::CheckPidDead {
+                ..
+            }
+            | Mortal::CheckPidfileMalformed {
+                ..
+            }
+            | Mortal::CheckPidfileUnaccessible {
+                ..
+            } => Some("pid"),
+            Mortal::CheckURL {
+                ..
+            }
+            | Mortal::CheckURLFail {
+                ..
+            }
+            | Mortal::CheckURLAssertion {
+                ..
+            } => Some("urls"),
+            Mortal::CheckTcp {
+                ..
+            } => Some("tcp"),
+            Mortal::CheckUnexpectedResponse {
+                check,
+                ..
+            } => Some(check),
+            Mortal::CheckTlsExpiry {
+                ..
+            } => Some("tls"),
+            _ => None,
+        }
+    }
+
+
+    fn code(&self) -> u16 {
+        match self {
+            Mortal::CheckNoServiceChecks {
+                ..
+            } => 200,
+            Mortal::CheckPidDead {
+                ..
+            } => 201,
+            Mortal::CheckURL {
+                ..
+            } => 202,
+            Mortal::CheckURLFail {
+                ..
+            } => 203,
+            Mortal::CheckPidfileMalformed {
+                ..
+            } => 204,
+            Mortal::CheckPidfileUnaccessible {
+                ..
+            } => 205,
+            Mortal::CheckUnixSocket {
+                ..
+            } => 206,
+            Mortal::CheckUnixSocketMissing {
+                ..
+            } => 207,
+            Mortal::CheckDiskSpace {
+                ..
+            } => 208,
+            Mortal::CheckDiskInodes {
+                ..
+            } => 209,
+            Mortal::CheckTcp {
+                ..
+            } => 210,
+            Mortal::CheckUnexpectedResponse {
+                ..
+            } => 211,
+            Mortal::CheckURLAssertion {
+                ..
+            } => 212,
+            Mortal::CheckTlsExpiry {
+                ..
+            } => 213,
+            Mortal::RawLoadFailure {
+                ..
+            } => 300,
+            Mortal::RawAccessFailure {
+                ..
+            } => 301,
+            Mortal::DefinitionDecodeFailure {
+                ..
+            } => 302,
+            Mortal::DefinitionLoadFailure {
+                ..
+            } => 303,
+            Mortal::DefinitionUnknownKeys {
+                ..
+            } => 304,
+            Mortal::DefinitionInvalid {
+                ..
+            } => 305,
+            Mortal::ServiceNotFound {
+                ..
+            } => 400,
+            Mortal::ServiceNoStartDefined {
+                ..
+            } => 401,
+            Mortal::ServiceStartFailure {
+                ..
+            } => 402,
+            Mortal::ServiceNoReloadDefined {
+                ..
+            } => 403,
+            Mortal::ServiceValidateFailure {
+                ..
+            } => 404,
+            Mortal::ServiceConfigureFailure {
+                ..
+            } => 405,
+            Mortal::ServiceAfterStartFailure {
+                ..
+            } => 406,
+            Mortal::ServiceStopFailure {
+                ..
+            } => 407,
+            Mortal::ServiceAfterStopFailure {
+                ..
+            } => 408,
+            Mortal::ServiceReloadFailure {
+                ..
+            } => 409,
+            Mortal::ServiceWaitingForDependency {
+                ..
+            } => 410,
+            Mortal::ServiceRestartBackedOff {
+                ..
+            } => 411,
+            Mortal::ServiceGivenUp {
+                ..
+            } => 412,
+            Mortal::ServiceForegroundUnsupervised {
+                ..
+            } => 413,
+            Mortal::NotificationConfigFailure {
+                ..
+            } => 500,
+            Mortal::NotificationFailure {
+                ..
+            } => 501,
+            Mortal::NotifierFailure {
+                ..
+            } => 502,
+            Mortal::NotifierUnknown {
+                ..
+            } => 503,
+            Mortal::NotifiersDecodeFailure {
+                ..
+            } => 504,
+            Mortal::ControlSocketUnavailable {
+                ..
+            } => 600,
+            Mortal::ControlRequestFailure {
+                ..
+            } => 601,
+            Mortal::SanityCheckFailure {
+                ..
+            } => 900,
+        }
+    }
+
+
+    fn service(&self) -> Option<&Service> {
+        match self {
+            Mortal::CheckNoServiceChecks {
+                service,
+                ..
+            }
+            | Mortal::CheckPidDead {
+                service,
+                ..
+            }
+            | Mortal::CheckURL {
+                service,
+                ..
+            }
+            | Mortal::CheckURLFail {
+                service,
+                ..
+            }
+            | Mortal::CheckPidfileMalformed {
+                service,
+                ..
+            }
+            | Mortal::CheckPidfileUnaccessible {
+                service,
+                ..
+            }
+            | Mortal::CheckUnixSocket {
+                service,
+                ..
+            }
+            | Mortal::CheckUnixSocketMissing {
+                service,
+                ..
+            }
+            | Mortal::CheckDiskSpace {
+                service,
+                ..
+            }
+            | Mortal::CheckDiskInodes {
+                service,
+                ..
+            }
+            | Mortal::CheckTcp {
+                service,
+                ..
+            }
+            | Mortal::CheckUnexpectedResponse {
+                service,
+                ..
+            }
+            | Mortal::CheckURLAssertion {
+                service,
+                ..
+            }
+            | Mortal::CheckTlsExpiry {
+                service,
+                ..
+            }
+            | Mortal::ServiceNoStartDefined {
+                service,
+                ..
+            }
+            | Mortal::ServiceStartFailure {
+                service,
+                ..
+            }
+            | Mortal::ServiceNoReloadDefined {
+                service,
+                ..
+            }
+            | Mortal::ServiceValidateFailure {
+                service,
+                ..
+            }
+            | Mortal::ServiceConfigureFailure {
+                service,
+                ..
+            }
+            | Mortal::ServiceAfterStartFailure {
+                service,
+                ..
+            }
+            | Mortal::ServiceStopFailure {
+                service,
+                ..
+            }
+            | Mortal::ServiceAfterStopFailure {
+                service,
+                ..
+            }
+            | Mortal::ServiceReloadFailure {
+                service,
+                ..
+            }
+            | Mortal::ServiceWaitingForDependency {
+                service,
+                ..
+            }
+            | Mortal::ServiceRestartBackedOff {
+                service,
+                ..
+            }
+            | Mortal::ServiceGivenUp {
+                service,
+                ..
+            }
+            | Mortal::ServiceForegroundUnsupervised {
+                service,
+            }
+            | Mortal::NotificationConfigFailure {
+                service,
+                ..
+            }
+            | Mortal::NotifierUnknown {
+                service,
+                ..
+            } => Some(service),
+            _ => None,
+        }
+    }
+
+
+    fn cause(&self) -> Option<Value> {
+        match self {
+            Mortal::RawLoadFailure {
+                cause,
+                ..
+            }
+            | Mortal::RawAccessFailure {
+                cause,
+                ..
+            }
+            | Mortal::DefinitionLoadFailure {
+                cause,
+                ..
+            }
+            | Mortal::CheckPidfileUnaccessible {
+                cause,
+                ..
+            }
+            | Mortal::CheckUnixSocket {
+                cause,
+                ..
+            }
+            | Mortal::CheckUnixSocketMissing {
+                cause,
+                ..
+            }
+            | Mortal::CheckTcp {
+                cause,
+                ..
+            }
+            | Mortal::ServiceStartFailure {
+                cause,
+                ..
+            }
+            | Mortal::ServiceValidateFailure {
+                cause,
+                ..
+            }
+            | Mortal::ServiceConfigureFailure {
+                cause,
+                ..
+            }
+            | Mortal::ServiceAfterStartFailure {
+                cause,
+                ..
+            }
+            | Mortal::ServiceStopFailure {
+                cause,
+                ..
+            }
+            | Mortal::ServiceAfterStopFailure {
+                cause,
+                ..
+            }
+            | Mortal::ServiceReloadFailure {
+                cause,
+                ..
+            }
+            | Mortal::NotificationConfigFailure {
+                cause,
+                ..
+            } => Some(io_cause(cause)),
+            Mortal::CheckURL {
+                cause,
+                ..
+            }
+            | Mortal::CheckURLFail {
+                cause,
+                ..
+            } => Some(curl_cause(cause)),
+            Mortal::DefinitionDecodeFailure {
+                cause,
+                ..
+            }
+            | Mortal::NotifiersDecodeFailure {
+                cause,
+                ..
+            } => {
+                Some(json!({
+                    "type": "toml",
+                    "message": cause.to_string(),
+                }))
+            }
+            Mortal::NotificationFailure {
+                cause,
+            } => {
+                Some(json!({
+                    "type": "slack",
+                    "message": cause.to_string(),
+                }))
+            }
+            Mortal::NotifierFailure {
+                cause,
+                ..
+            } => {
+                Some(json!({
+                    "type": "notifier",
+                    "message": cause,
+                }))
+            }
+            _ => None,
+        }
+    }
+
+
+    fn fields(&self) -> Value {
+        match self {
+            Mortal::CheckPidDead {
+                pid,
+                ..
+            } => json!({ "pid": pid }),
+            Mortal::RawLoadFailure {
+                file_name,
+                ..
+            }
+            | Mortal::RawAccessFailure {
+                file_name,
+                ..
+            }
+            | Mortal::NotifiersDecodeFailure {
+                file_name,
+                ..
+            } => json!({ "file_name": file_name }),
+            Mortal::DefinitionDecodeFailure {
+                ini_name,
+                line,
+                column,
+                key,
+                snippet,
+                ..
+            } => {
+                json!({
+                    "ini_name": ini_name,
+                    "line": line,
+                    "column": column,
+                    "key": key,
+                    "snippet": snippet,
+                })
+            }
+            Mortal::DefinitionLoadFailure {
+                ini_name,
+                ..
+            } => json!({ "ini_name": ini_name }),
+            Mortal::DefinitionUnknownKeys {
+                ini_name,
+                keys,
+            } => {
+                json!({
+                    "ini_name": ini_name,
+                    "keys": keys
+                        .iter()
+                        .map(|(key, suggestion)| {
+                            json!({ "key": key, "suggestion": suggestion })
+                        })
+                        .collect::<Vec<Value>>(),
+                })
+            }
+            Mortal::DefinitionInvalid {
+                ini_name,
+                key,
+                ..
+            } => json!({ "ini_name": ini_name, "key": key }),
+            Mortal::CheckURL {
+                url,
+                ..
+            } => json!({ "url": url }),
+            Mortal::CheckTcp {
+                address,
+                ..
+            } => json!({ "address": address }),
+            Mortal::CheckUnexpectedResponse {
+                target,
+                expected,
+                response,
+                ..
+            } => json!({ "target": target, "expected": expected, "response": response }),
+            Mortal::CheckURLAssertion {
+                url,
+                status,
+                latency,
+                reason,
+                ..
+            } => {
+                json!({
+                    "url": url,
+                    "status": status,
+                    "latency_ms": latency,
+                    "reason": reason,
+                })
+            }
+            Mortal::CheckTlsExpiry {
+                url,
+                days,
+                expires,
+                ..
+            } => json!({ "url": url, "days": days, "expires": expires }),
+            Mortal::ServiceNotFound {
+                name,
+                services_dir,
+            } => json!({ "name": name, "services_dir": services_dir }),
+            Mortal::ServiceStartFailure {
+                output,
+                ..
+            } => json!({ "output": output }),
+            Mortal::ServiceWaitingForDependency {
+                dependency,
+                reason,
+                ..
+            } => json!({ "dependency": dependency, "reason": reason }),
+            Mortal::ServiceRestartBackedOff {
+                remaining,
+                ..
+            } => json!({ "remaining": remaining }),
+            Mortal::ServiceGivenUp {
+                restarts,
+                ..
+            } => json!({ "restarts": restarts }),
+            Mortal::NotifierFailure {
+                notifier,
+                ..
+            }
+            | Mortal::NotifierUnknown {
+                notifier,
+                ..
+            } => json!({ "notifier": notifier }),
+            Mortal::ControlSocketUnavailable {
+                control_socket,
+            } => json!({ "control_socket": control_socket }),
+            Mortal::ControlRequestFailure {
+                message,
+            }
+            | Mortal::SanityCheckFailure {
+                message,
+            } => json!({ "message": message }),
+            _ => json!({}),
+        }
+    }
+
+
+    fn variant(&self) -> &'static str {
+        match self {
+            Mortal::CheckNoServiceChecks {
+                ..
+            } => "CheckNoServiceChecks",
+            Mortal::CheckPidDead {
+                ..
+            } => "CheckPidDead",
+            Mortal::RawLoadFailure {
+                ..
+            } => "RawLoadFailure",
+            Mortal::RawAccessFailure {
+                ..
+            } => "RawAccessFailure",
+            Mortal::DefinitionDecodeFailure {
+                ..
+            } => "DefinitionDecodeFailure",
+            Mortal::DefinitionLoadFailure {
+                ..
+            } => "DefinitionLoadFailure",
+            Mortal::DefinitionUnknownKeys {
+                ..
+            } => "DefinitionUnknownKeys",
+            Mortal::DefinitionInvalid {
+                ..
+            } => "DefinitionInvalid",
+            Mortal::CheckURL {
+                ..
+            } => "CheckURL",
+            Mortal::CheckURLFail {
+                ..
+            } => "CheckURLFail",
+            Mortal::CheckPidfileMalformed {
+                ..
+            } => "CheckPidfileMalformed",
+            Mortal::CheckPidfileUnaccessible {
+                ..
+            } => "CheckPidfileUnaccessible",
+            Mortal::CheckUnixSocket {
+                ..
+            } => "CheckUnixSocket",
+            Mortal::CheckUnixSocketMissing {
+                ..
+            } => "CheckUnixSocketMissing",
+            Mortal::CheckDiskSpace {
+                ..
+            } => "CheckDiskSpace",
+            Mortal::CheckDiskInodes {
+                ..
+            } => "CheckDiskInodes",
+            Mortal::CheckTcp {
+                ..
+            } => "CheckTcp",
+            Mortal::CheckUnexpectedResponse {
+                ..
+            } => "CheckUnexpectedResponse",
+            Mortal::CheckURLAssertion {
+                ..
+            } => "CheckURLAssertion",
+            Mortal::CheckTlsExpiry {
+                ..
+            } => "CheckTlsExpiry",
+            Mortal::ServiceNotFound {
+                ..
+            } => "ServiceNotFound",
+            Mortal::ServiceNoStartDefined {
+                ..
+            } => "ServiceNoStartDefined",
+            Mortal::ServiceStartFailure {
+                ..
+            } => "ServiceStartFailure",
+            Mortal::ServiceNoReloadDefined {
+                ..
+            } => "ServiceNoReloadDefined",
+            Mortal::ServiceValidateFailure {
+                ..
+            } => "ServiceValidateFailure",
+            Mortal::ServiceConfigureFailure {
+                ..
+            } => "ServiceConfigureFailure",
+            Mortal::ServiceAfterStartFailure {
+                ..
+            } => "ServiceAfterStartFailure",
+            Mortal::ServiceStopFailure {
+                ..
+            } => "ServiceStopFailure",
+            Mortal::ServiceAfterStopFailure {
+                ..
+            } => "ServiceAfterStopFailure",
+            Mortal::ServiceReloadFailure {
+                ..
+            } => "ServiceReloadFailure",
+            Mortal::ServiceWaitingForDependency {
+                ..
+            } => "ServiceWaitingForDependency",
+            Mortal::ServiceRestartBackedOff {
+                ..
+            } => "ServiceRestartBackedOff",
+            Mortal::ServiceGivenUp {
+                ..
+            } => "ServiceGivenUp",
+            Mortal::ServiceForegroundUnsupervised {
+                ..
+            } => "ServiceForegroundUnsupervised",
+            Mortal::NotificationConfigFailure {
+                ..
+            } => "NotificationConfigFailure",
+            Mortal::NotificationFailure {
+                ..
+            } => "NotificationFailure",
+            Mortal::NotifierFailure {
+                ..
+            } => "NotifierFailure",
+            Mortal::NotifierUnknown {
+                ..
+            } => "NotifierUnknown",
+            Mortal::NotifiersDecodeFailure {
+                ..
+            } => "NotifiersDecodeFailure",
+            Mortal::ControlSocketUnavailable {
+                ..
+            } => "ControlSocketUnavailable",
+            Mortal::ControlRequestFailure {
+                ..
+            } => "ControlRequestFailure",
+            Mortal::SanityCheckFailure {
+                ..
+            } => "SanityCheckFailure",
+        }
+    }
+}
+
+
+impl Mortal {
+    /// true if Mortal is a disk space or inodes check failure - not fixable by restart
+    pub fn is_disk_issue(&self) -> bool {
+        matches!(
+            self,
+            Mortal::CheckDiskSpace { .. } | Mortal::CheckDiskInodes { .. }
+        )
+    }
+
+
+    /// true if Mortal is a warning - which requires notification, but not restart of service
+    pub fn is_warning(&self) -> bool {
+        self.is_disk_issue() || matches!(self, Mortal::CheckTlsExpiry { .. })
+    }
+
+
+    /// returns key of alert about Mortal. Repeated alerts with the same key are suppressed
+    pub fn alert_key(&self) -> String {
+        match self {
+            Mortal::CheckTlsExpiry {
+                url,
+                ..
+            } => format!("{}: {}", self.variant(), url),
+            _ => self.variant().to_string(),
+        }
+    }
+
+
+    /// true if Mortal is a check failure which requires restart of service
+    pub fn should_restart(&self) -> bool {
+        self.kind() == MortalKind::CheckFailure
+            && !self.is_warning()
+            && !matches!(self, Mortal::CheckNoServiceChecks { .. })
+    }
+}
+
+
+impl StdError for Mortal {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        match self {
+            Mortal::RawLoadFailure {
+                cause,
+                ..
+            }
+            | Mortal::RawAccessFailure {
+                cause,
+                ..
+            }
+            | Mortal::DefinitionLoadFailure {
+                cause,
+                ..
+            }
+            | Mortal::CheckPidfileUnaccessible {
+                cause,
+                ..
+            }
+            | Mortal::CheckUnixSocket {
+                cause,
+                ..
+            }
+            | Mortal::CheckUnixSocketMissing {
+                cause,
+                ..
+            }
+            | Mortal::CheckTcp {
+                cause,
+                ..
+            }
+            | Mortal::ServiceStartFailure {
+                cause,
+                ..
+            }
+            | Mortal::ServiceValidateFailure {
+                cause,
+                ..
+            }
+            | Mortal::ServiceConfigureFailure {
+                cause,
+                ..
+            }
+            | Mortal::ServiceAfterStartFailure {
+                cause,
+                ..
+            }
+            | Mortal::ServiceStopFailure {
+                cause,
+                ..
+            }
+            | Mortal::ServiceAfterStopFailure {
+                cause,
+                ..
+            }
+            | Mortal::ServiceReloadFailure {
+                cause,
+                ..
+            }
+            | Mortal::NotificationConfigFailure {
+                cause,
+                ..
+            } => Some(cause),
+            Mortal::CheckURL {
+                cause,
+                ..
+            }
+            | Mortal::CheckURLFail {
+                cause,
+                ..
+            } => Some(cause),
+            Mortal::DefinitionDecodeFailure {
+                cause,
+                ..
+            }
+            | Mortal::NotifiersDecodeFailure {
+                cause,
+                ..
+            } => Some(cause),
+            Mortal::NotificationFailure {
+                cause,
+            } => Some(cause),
+            _ => None,
+        }
+    }
+}
+
+
 impl Display for Mortal {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(
             f,
             "Mortal: {}",
             match *self {
-                Mortal::OkAllChecks {
-                    ref service,
-                    ref amount,
-                } => {
-                    format!(
-                        "Ok: {} successfully passed all: {} checks!",
-                        service, amount
-                    )
-                }
-                Mortal::OkUrlsChecks {
-                    ref service,
-                } => {
-                    format!("Ok: {} successfully passed URLs checks!", service)
-                }
-                Mortal::OkUnixSockCheck {
-                    ref service,
-                } => {
-                    format!("Ok: {} successfully passed UNIX sock checks!", service)
-                }
-                Mortal::OkPidAlive {
-                    ref service,
-                    ref pid,
-                } => {
-                    format!("Ok: Alive pid: {} of service: {}", pid, service)
-                }
-                Mortal::OkPidInterrupted {
-                    ref service,
-                    ref pid,
-                } => {
-                    format!("Ok: Interrupted pid: {} of service: {}", pid, service)
-                }
-                Mortal::OkPidAlreadyInterrupted {
-                    ref service,
-                    ref pid,
-                } => {
-                    format!(
-                        "Ok: Already interrupted pid: {} of service: {}",
-                        pid, service
-                    )
-                }
-                Mortal::OkDiskCheck {
-                    ref service,
-                } => {
-                    format!("Ok: Disk check passed for service: {}", service)
-                }
 
                 Mortal::CheckNoServiceChecks {
                     ref service,
@@ -194,11 +1402,30 @@ impl Display for Mortal {
                 }
                 Mortal::DefinitionDecodeFailure {
                     ref ini_name,
+                    ref line,
+                    ref column,
+                    ref key,
+                    ref snippet,
                     ref cause,
                 } => {
                     format!(
-                        "Failed to decode definition from ini: {}. Reason: {}!",
-                        ini_name, cause
+                        "Failed to decode definition from ini: {}{}{}. Reason: {}!{}",
+                        ini_name,
+                        match (line, column) {
+                            (Some(line), Some(column)) => {
+                                format!(" at line: {}, column: {}", line, column)
+                            }
+                            _ => "".to_string(),
+                        },
+                        match key {
+                            Some(key) => format!(", key: {}", key),
+                            None => "".to_string(),
+                        },
+                        cause,
+                        match snippet.as_ref() {
+                            "" => "".to_string(),
+                            snippet => format!("\n{}", snippet),
+                        }
                     )
                 }
                 Mortal::DefinitionLoadFailure {
@@ -210,6 +1437,37 @@ impl Display for Mortal {
                         ini_name, cause
                     )
                 }
+                Mortal::DefinitionUnknownKeys {
+                    ref ini_name,
+                    ref keys,
+                } => {
+                    format!(
+                        "Unknown keys in definition from ini: {}: {}! (set 'strict = false' \
+                         to allow them)",
+                        ini_name,
+                        keys.iter()
+                            .map(|(key, suggestion)| {
+                                match suggestion {
+                                    Some(field) => {
+                                        format!("{} (did you mean {}?)", key, field)
+                                    }
+                                    None => key.to_string(),
+                                }
+                            })
+                            .collect::<Vec<String>>()
+                            .join(", ")
+                    )
+                }
+                Mortal::DefinitionInvalid {
+                    ref ini_name,
+                    ref key,
+                    ref reason,
+                } => {
+                    format!(
+                        "Invalid definition from ini: {}, key: {}. Reason: {}!",
+                        ini_name, key, reason
+                    )
+                }
 
                 Mortal::CheckURL {
                     ref service,
@@ -279,7 +1537,59 @@ impl Display for Mortal {
                         service.disk_minimum_inodes()
                     )
                 }
+                Mortal::CheckTcp {
+                    ref service,
+                    ref address,
+                    ref cause,
+                } => {
+                    format!(
+                        "Failed to connect to TCP address: {} of: {}. Reason: {}!",
+                        address, service, cause
+                    )
+                }
+                Mortal::CheckUnexpectedResponse {
+                    ref service,
+                    ref target,
+                    ref expected,
+                    ref response,
+                    ..
+                } => {
+                    format!(
+                        "Unexpected response: {:?} from: {} of: {}. Expected: {}",
+                        response, target, service, expected
+                    )
+                }
+                Mortal::CheckURLAssertion {
+                    ref service,
+                    ref url,
+                    ref status,
+                    ref latency,
+                    ref reason,
+                } => {
+                    format!(
+                        "URL check failed for: {} of: {} (status: {}, latency: {} ms). \
+                         Reason: {}!",
+                        url, service, status, latency, reason
+                    )
+                }
+                Mortal::CheckTlsExpiry {
+                    ref service,
+                    ref url,
+                    ref days,
+                    ref expires,
+                } => {
+                    format!(
+                        "TLS certificate of: {} of: {} expires in: {} days (on: {})!",
+                        url, service, days, expires
+                    )
+                }
 
+                Mortal::ServiceNotFound {
+                    ref name,
+                    ref services_dir,
+                } => {
+                    format!("No service: {} defined in: {}!", name, services_dir)
+                }
                 Mortal::ServiceNoStartDefined {
                     ref service,
                 } => {
@@ -288,15 +1598,112 @@ impl Display for Mortal {
                 Mortal::ServiceStartFailure {
                     ref service,
                     ref cause,
+                    ref output,
                 } => {
                     format!(
-                        "Failed to launch commands: {} for {}! Reason: {}",
+                        "Failed to launch commands: {} for {}! Reason: {}{}",
                         service
                             .clone()
                             .start
                             .unwrap_or_else(|| "#no-commands".to_string()),
                         service,
-                        cause
+                        cause,
+                        match output.len() {
+                            0 => "".to_string(),
+                            _ => format!("\nLast output:\n{}", output.join("\n")),
+                        }
+                    )
+                }
+                Mortal::ServiceNoReloadDefined {
+                    ref service,
+                } => {
+                    format!("No 'reload' value in configuration of: {}!", service)
+                }
+                Mortal::ServiceValidateFailure {
+                    ref service,
+                    ref cause,
+                } => {
+                    format!(
+                        "Validation of: {} failed! Service won't be started. Reason: {}",
+                        service, cause
+                    )
+                }
+                Mortal::ServiceConfigureFailure {
+                    ref service,
+                    ref cause,
+                } => {
+                    format!(
+                        "Failed to configure: {}! Service won't be started. Reason: {}",
+                        service, cause
+                    )
+                }
+                Mortal::ServiceAfterStartFailure {
+                    ref service,
+                    ref cause,
+                } => {
+                    format!(
+                        "Failed 'after_start' commands of: {}! Reason: {}",
+                        service, cause
+                    )
+                }
+                Mortal::ServiceStopFailure {
+                    ref service,
+                    ref cause,
+                } => {
+                    format!("Failed to stop: {}! Reason: {}", service, cause)
+                }
+                Mortal::ServiceAfterStopFailure {
+                    ref service,
+                    ref cause,
+                } => {
+                    format!(
+                        "Failed 'after_stop' commands of: {}! Reason: {}",
+                        service, cause
+                    )
+                }
+                Mortal::ServiceReloadFailure {
+                    ref service,
+                    ref cause,
+                } => {
+                    format!("Failed to reload: {}! Reason: {}", service, cause)
+                }
+                Mortal::ServiceWaitingForDependency {
+                    ref service,
+                    ref dependency,
+                    ref reason,
+                } => {
+                    format!(
+                        "Service: {} is waiting for dependency: {}. Reason: {}",
+                        service, dependency, reason
+                    )
+                }
+                Mortal::ServiceRestartBackedOff {
+                    ref service,
+                    ref remaining,
+                } => {
+                    format!(
+                        "Restart of: {} is backed off for: {} ms more",
+                        service, remaining
+                    )
+                }
+                Mortal::ServiceGivenUp {
+                    ref service,
+                    ref restarts,
+                } => {
+                    format!(
+                        "Gave up restarting: {} after {} restarts within {} ms! Automatic \
+                         restarts are disabled until it's reset",
+                        service,
+                        restarts,
+                        service.restarts_window()
+                    )
+                }
+                Mortal::ServiceForegroundUnsupervised {
+                    ref service,
+                } => {
+                    format!(
+                        "Foreground service: {} can only be started by running {}!",
+                        service, NAME
                     )
                 }
 
@@ -305,6 +1712,33 @@ impl Display for Mortal {
                 } => {
                     format!("Failed to send notification! Reason: {}", cause)
                 }
+                Mortal::NotifierFailure {
+                    ref notifier,
+                    ref cause,
+                } => {
+                    format!(
+                        "Failed to send notification via {}! Reason: {}",
+                        notifier, cause
+                    )
+                }
+                Mortal::NotifierUnknown {
+                    ref service,
+                    ref notifier,
+                } => {
+                    format!(
+                        "Service: {} routes notifications to undefined notifier: {}!",
+                        service, notifier
+                    )
+                }
+                Mortal::NotifiersDecodeFailure {
+                    ref file_name,
+                    ref cause,
+                } => {
+                    format!(
+                        "Failed to decode notifiers from: {}. Reason: {}!",
+                        file_name, cause
+                    )
+                }
                 Mortal::NotificationConfigFailure {
                     ref service,
                     ref cause,
@@ -315,6 +1749,20 @@ impl Display for Mortal {
                     )
                 }
 
+                Mortal::ControlSocketUnavailable {
+                    ref control_socket,
+                } => {
+                    format!(
+                        "No running {} listening on control socket: {}!",
+                        NAME, control_socket
+                    )
+                }
+                Mortal::ControlRequestFailure {
+                    ref message,
+                } => {
+                    format!("Control request failed: {}", message)
+                }
+
                 Mortal::SanityCheckFailure {
                     ref message,
                 } => {
@@ -324,3 +1772,34 @@ impl Display for Mortal {
         )
     }
 }
+
+
+/// outcome serialized with its code, kind, service name and structured cause
+impl Serialize for dyn Outcome + '_ {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(8))?;
+        map.serialize_entry("code", &self.code())?;
+        map.serialize_entry("variant", self.variant())?;
+        map.serialize_entry("kind", &self.kind())?;
+        map.serialize_entry("service", &self.service().map(|service| service.name()))?;
+        map.serialize_entry("check", &self.check())?;
+        map.serialize_entry("message", &self.to_string())?;
+        map.serialize_entry("cause", &self.cause())?;
+        map.serialize_entry("fields", &self.fields())?;
+        map.end()
+    }
+}
+
+
+impl Serialize for Blessing {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        (self as &dyn Outcome).serialize(serializer)
+    }
+}
+
+
+impl Serialize for Mortal {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        (self as &dyn Outcome).serialize(serializer)
+    }
+}
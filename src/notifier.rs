@@ -0,0 +1,637 @@
+use curl::easy::{Easy, List};
+use serde_json::{json, Value};
+use slack_hook::{
+    AttachmentBuilder, Field, Parse, PayloadBuilder, Slack, SlackTextContent::Text,
+};
+use std::{
+    collections::BTreeMap,
+    io::{prelude::*, BufReader, Error, ErrorKind},
+    net::{TcpStream, ToSocketAddrs},
+    path::Path,
+    process::{Command, Stdio},
+    time::Duration,
+};
+use toml::de::Error as TomlError;
+
+use crate::{
+    common::*,
+    mortal::Mortal::{self, *},
+    service::Service,
+    *,
+};
+
+
+/// default path to sendmail binary
+static SENDMAIL: &str = "/usr/sbin/sendmail";
+
+
+/// notification event - passed to notifiers, serialized as JSON for webhooks and commands
+#[derive(Serialize, Debug, Clone)]
+pub struct Event {
+    /// kind of event: "alert" or "recovery"
+    pub kind: String,
+    pub title: String,
+    pub summary: String,
+    pub color: String,
+    pub emoji: String,
+    pub message: String,
+
+    /// name of service
+    pub service: String,
+    pub service_details: String,
+    pub details_name: String,
+    pub details: String,
+    pub hostname: String,
+
+    /// system / release / machine / Deities version
+    pub system: String,
+    pub timestamp: String,
+
+    /// serialized Mortal which caused alert (if any)
+    pub mortal: Option<Value>,
+}
+
+
+impl Event {
+    /// returns plain text form of event
+    pub fn text(&self) -> String {
+        format!(
+            "{}\n\nMessage: {}\nService details: {}\nHost name: {}\n\
+             System / Release / Machine / {}: {}\n\n{}\n{}\n\n{}",
+            self.summary,
+            self.message,
+            self.service_details,
+            self.hostname,
+            NAME,
+            self.system,
+            self.details_name,
+            self.details,
+            self.timestamp
+        )
+    }
+
+
+    /// returns mail subject of event
+    pub fn subject(&self) -> String {
+        format!("[{}] {}: {}", NAME, self.title, self.message)
+    }
+}
+
+
+// Notifier delivers notification events to a single destination.
+//
+pub trait Notifier {
+    /// sends event. Returns description of result
+    fn notify(&self, event: &Event) -> Result<String, Mortal>;
+}
+
+
+/// Slack incoming webhook
+#[derive(Deserialize, Debug, Clone, Default)]
+#[serde(deny_unknown_fields)]
+pub struct SlackNotifier {
+    pub webhook_url: String,
+    pub channel: Option<String>,
+}
+
+
+/// generic webhook - receives event as JSON in POST request
+#[derive(Deserialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct WebhookNotifier {
+    pub url: String,
+}
+
+
+/// Mattermost (or other Slack-compatible) incoming webhook
+#[derive(Deserialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct MattermostNotifier {
+    pub url: String,
+    pub channel: Option<String>,
+}
+
+
+/// Discord webhook
+#[derive(Deserialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct DiscordNotifier {
+    pub url: String,
+}
+
+
+/// email sent through local sendmail binary
+#[derive(Deserialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct SendmailNotifier {
+    pub to: Vec<String>,
+    pub from: Option<String>,
+
+    /// path to sendmail binary (SENDMAIL by default)
+    pub sendmail: Option<String>,
+}
+
+
+/// email sent through SMTP relay (without TLS and authentication), like: localhost:25.
+/// Use sendmail notifier for anything more sophisticated
+#[derive(Deserialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct SmtpNotifier {
+    pub server: String,
+    pub to: Vec<String>,
+    pub from: String,
+}
+
+
+/// local command - receives event as JSON on its standard input
+#[derive(Deserialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct CommandNotifier {
+    pub command: String,
+}
+
+
+/// named notifier of global notifiers config
+#[derive(Deserialize, Debug, Clone)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum NotifierConfig {
+    Slack(SlackNotifier),
+    Webhook(WebhookNotifier),
+    Mattermost(MattermostNotifier),
+    Discord(DiscordNotifier),
+    Sendmail(SendmailNotifier),
+    Smtp(SmtpNotifier),
+    Command(CommandNotifier),
+}
+
+
+impl NotifierConfig {
+    /// returns notifier of config
+    pub fn notifier(&self) -> &dyn Notifier {
+        match self {
+            NotifierConfig::Slack(notifier) => notifier,
+            NotifierConfig::Webhook(notifier) => notifier,
+            NotifierConfig::Mattermost(notifier) => notifier,
+            NotifierConfig::Discord(notifier) => notifier,
+            NotifierConfig::Sendmail(notifier) => notifier,
+            NotifierConfig::Smtp(notifier) => notifier,
+            NotifierConfig::Command(notifier) => notifier,
+        }
+    }
+}
+
+
+/// loads named notifiers from given file. Missing file means no notifiers
+#[instrument]
+pub fn load(file_name: &str) -> Result<BTreeMap<String, NotifierConfig>, Mortal> {
+    if !Path::new(file_name).exists() {
+        return Ok(BTreeMap::new());
+    }
+    let content = Service::load_raw(file_name.to_string())?;
+    toml::from_str(&content).map_err(|cause: TomlError| {
+        NotifiersDecodeFailure {
+            file_name: file_name.to_string(),
+            cause,
+        }
+    })
+}
+
+
+/// returns failure of notifier with given kind
+fn failure(notifier: &str, cause: impl ToString) -> Mortal {
+    NotifierFailure {
+        notifier: notifier.to_string(),
+        cause: cause.to_string(),
+    }
+}
+
+
+/// sends JSON payload in POST request to given url
+#[instrument(skip(payload))]
+fn post_json(notifier: &str, url: &str, payload: &Value) -> Result<String, Mortal> {
+    let timeout = Duration::from_millis(NOTIFICATION_TIMEOUT);
+    let mut headers = List::new();
+    let mut easy = Easy::new();
+    let response_code = headers
+        .append("Content-Type: application/json")
+        .and_then(|_| easy.http_headers(headers))
+        .and_then(|_| easy.url(url))
+        .and_then(|_| easy.connect_timeout(timeout))
+        .and_then(|_| easy.timeout(timeout))
+        .and_then(|_| easy.follow_location(true))
+        .and_then(|_| easy.post(true))
+        .and_then(|_| easy.post_fields_copy(payload.to_string().as_bytes()))
+        .and_then(|_| easy.perform())
+        .and_then(|_| easy.response_code())
+        .map_err(|cause| failure(notifier, cause))?;
+    match response_code {
+        200..=299 => Ok(format!("Notification sent via {}", notifier)),
+        code => {
+            Err(failure(
+                notifier,
+                format!("Webhook responded with: {}", code),
+            ))
+        }
+    }
+}
+
+
+/// returns fields of event in form of Slack attachment fields
+fn chat_fields(event: &Event) -> Value {
+    json!([
+        {"title": "Message:", "value": event.message, "short": true},
+        {"title": "Service details:", "value": event.service_details, "short": true},
+        {"title": "Host name:", "value": event.hostname, "short": true},
+        {
+            "title": format!("System / Release / Machine / {}", NAME),
+            "value": event.system,
+            "short": true
+        },
+        {"title": event.details_name, "value": event.details, "short": false},
+    ])
+}
+
+
+impl Notifier for SlackNotifier {
+    #[instrument(skip(event))]
+    fn notify(&self, event: &Event) -> Result<String, Mortal> {
+        let channel = self
+            .channel
+            .clone()
+            .unwrap_or_else(|| SLACK_ALERT_CHANNEL.to_string());
+        match (&self.webhook_url[..], &channel[..]) {
+            ("", _) => {
+                info!("SLACK_WEBHOOKURL is unset. Slack notifications will NOT be sent!");
+                Ok("Notifiication skipped".to_string())
+            }
+            (_, "") => {
+                info!("SLACK_ALERTCHANNEL is empty. Slack notigications will NOTE be sent!");
+                Ok("Notifiication skipped".to_string())
+            }
+            (webhook_url, _channel) => {
+                let slack = Slack::new(webhook_url).map_err(|cause| {
+                    NotificationFailure {
+                        cause,
+                    }
+                })?;
+                let p = PayloadBuilder::new()
+                    .attachments(
+                        vec![
+                            AttachmentBuilder::new(DEFAULT_NOTIFICATION_NAME)
+                                .title(event.title.clone())
+                                .author_name(DEFAULT_NOTIFICATION_NAME)
+                                .author_icon(DEFAULT_VKS_LOGO)
+                                .color(event.color.clone())
+                                .text(
+                                    vec![
+                                        Text(event.summary.clone().into()),
+                                        Text("".into()),
+                                    ].as_slice())
+                                .fields(
+                                    vec![
+                                        Field::new("", "", Some(false)),
+                                        Field::new("", "", Some(false)),
+                                        Field::new("Message:", event.message.clone(), Some(true)),
+                                        Field::new("Service details:", event.service_details.clone(), Some(true)),
+                                        Field::new("", "", Some(false)),
+                                        Field::new("Host name:", event.hostname.clone(), Some(true)),
+                                        Field::new(
+                                            format!("System / Release / Machine / {}", NAME),
+                                            event.system.clone(),
+                                            Some(true)),
+                                        Field::new("", "", Some(true)),
+                                        Field::new(event.details_name.clone(), event.details.clone(), Some(false)),
+                                    ])
+                                .footer_icon(DEFAULT_VKS_LOGO)
+                                .footer(vec![
+                                    Text(format!("{}   © 2o16-2o17   |", event.timestamp).into()),
+                                ].as_slice())
+                                .build()
+                                .map_err(|cause| NotificationFailure { cause })?
+                        ])
+                    .link_names(true)
+                    .unfurl_links(true)
+                    .unfurl_media(true)
+                    .username(DEFAULT_NOTIFICATION_NAME)
+                    .icon_url(DEFAULT_VKS_LOGO)
+                    .icon_emoji(event.emoji.clone())
+                    .text("")
+                    .channel(channel)
+                    .parse(Parse::Full)
+                    .build()
+                    .map_err(|cause| NotificationFailure { cause })?;
+
+                match slack.send(&p) {
+                    Ok(()) => Ok("Notifiication sent".to_string()),
+                    Err(cause) => {
+                        Err(NotificationFailure {
+                            cause,
+                        })
+                    }
+                }
+            }
+        }
+    }
+}
+
+
+impl Notifier for WebhookNotifier {
+    #[instrument(skip(event))]
+    fn notify(&self, event: &Event) -> Result<String, Mortal> {
+        let payload =
+            serde_json::to_value(event).map_err(|cause| failure("webhook", cause))?;
+        post_json("webhook", &self.url, &payload)
+    }
+}
+
+
+impl Notifier for MattermostNotifier {
+    #[instrument(skip(event))]
+    fn notify(&self, event: &Event) -> Result<String, Mortal> {
+        let mut payload = json!({
+            "username": DEFAULT_NOTIFICATION_NAME,
+            "icon_url": DEFAULT_VKS_LOGO,
+            "attachments": [{
+                "fallback": format!("{}: {}", event.title, event.message),
+                "color": event.color,
+                "title": event.title,
+                "text": event.summary,
+                "fields": chat_fields(event),
+                "footer": event.timestamp,
+            }],
+        });
+        if let Some(channel) = &self.channel {
+            payload["channel"] = json!(channel);
+        }
+        post_json("mattermost", &self.url, &payload)
+    }
+}
+
+
+impl Notifier for DiscordNotifier {
+    #[instrument(skip(event))]
+    fn notify(&self, event: &Event) -> Result<String, Mortal> {
+        let fields: Vec<Value> = chat_fields(event)
+            .as_array()
+            .into_iter()
+            .flatten()
+            .map(|field| {
+                json!({
+                    "name": field["title"],
+                    "value": field["value"],
+                    "inline": field["short"],
+                })
+            })
+            .collect();
+        let payload = json!({
+            "username": DEFAULT_NOTIFICATION_NAME,
+            "avatar_url": DEFAULT_VKS_LOGO,
+            "embeds": [{
+                "title": event.title,
+                "description": event.summary,
+                "color": u32::from_str_radix(event.color.trim_start_matches('#'), 16)
+                    .unwrap_or_default(),
+                "fields": fields,
+                "footer": {"text": event.timestamp},
+            }],
+        });
+        post_json("discord", &self.url, &payload)
+    }
+}
+
+
+/// mail header value in single line. Line breaks would inject headers or end them early
+fn header(value: &str) -> String {
+    value
+        .split(['\r', '\n'])
+        .map(str::trim)
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<&str>>()
+        .join(" ")
+}
+
+
+impl Notifier for SendmailNotifier {
+    #[instrument(skip(event))]
+    fn notify(&self, event: &Event) -> Result<String, Mortal> {
+        let sendmail = self
+            .sendmail
+            .clone()
+            .unwrap_or_else(|| SENDMAIL.to_string());
+        let mut message = format!("To: {}\n", header(&self.to.join(", ")));
+        if let Some(from) = &self.from {
+            message.push_str(&format!("From: {}\n", header(from)));
+        }
+        message.push_str(&format!(
+            "Subject: {}\n\n{}\n",
+            header(&event.subject()),
+            event.text()
+        ));
+
+        let mut child = Command::new(&sendmail)
+            .arg("-t")
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|cause| failure("sendmail", cause))?;
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin
+                .write_all(message.as_bytes())
+                .map_err(|cause| failure("sendmail", cause))?;
+        }
+        let output = child
+            .wait_with_output()
+            .map_err(|cause| failure("sendmail", cause))?;
+        match output.status.success() {
+            true => Ok(format!("Mail sent to: {}", self.to.join(", "))),
+            false => {
+                Err(failure(
+                    "sendmail",
+                    format!(
+                        "{} exited with: {}. {}",
+                        sendmail,
+                        output.status,
+                        String::from_utf8_lossy(&output.stderr).trim()
+                    ),
+                ))
+            }
+        }
+    }
+}
+
+
+impl SmtpNotifier {
+    /// performs SMTP dialog with server, delivering event as mail
+    #[instrument(skip(event))]
+    fn deliver(&self, event: &Event) -> Result<(), Error> {
+        let timeout = Duration::from_millis(NOTIFICATION_TIMEOUT);
+        let address = self
+            .server
+            .to_socket_addrs()?
+            .next()
+            .ok_or_else(|| Error::other(format!("Unresolved server: {}", self.server)))?;
+        let mut stream = TcpStream::connect_timeout(&address, timeout)?;
+        stream.set_read_timeout(Some(timeout))?;
+        stream.set_write_timeout(Some(timeout))?;
+        let mut reader = BufReader::new(stream.try_clone()?);
+        smtp_reply(&mut reader, 220)?;
+
+        let hostname = hostname::get()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_else(|_| DEFAULT_HOSTNAME.to_string());
+        let mut commands = vec![
+            (format!("HELO {}", hostname), 250),
+            (format!("MAIL FROM:<{}>", self.from), 250),
+        ];
+        for recipient in &self.to {
+            commands.push((format!("RCPT TO:<{}>", recipient), 250));
+        }
+        commands.push(("DATA".to_string(), 354));
+
+        // NOTE: lines starting with a dot have to be escaped, single dot ends the message:
+        let body: Vec<String> = event
+            .text()
+            .lines()
+            .map(|line| {
+                match line.starts_with('.') {
+                    true => format!(".{}", line),
+                    false => line.to_string(),
+                }
+            })
+            .collect();
+        commands.push((
+            format!(
+                "To: {}\r\nFrom: {}\r\nSubject: {}\r\n\r\n{}\r\n.",
+                header(&self.to.join(", ")),
+                header(&self.from),
+                header(&event.subject()),
+                body.join("\r\n")
+            ),
+            250,
+        ));
+        commands.push(("QUIT".to_string(), 221));
+
+        for (command, expected) in commands {
+            stream.write_all(format!("{}\r\n", command).as_bytes())?;
+            smtp_reply(&mut reader, expected)?;
+        }
+        Ok(())
+    }
+}
+
+
+/// reads (multiline) reply of SMTP server. Fails if its code isn't the expected one
+fn smtp_reply(reader: &mut impl BufRead, expected: u16) -> Result<(), Error> {
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line)? == 0 {
+            return Err(Error::other("Connection closed by server"));
+        }
+        // NOTE: "250-" continues multiline reply, "250 " ends it:
+        if line.get(3..4) == Some("-") {
+            continue;
+        }
+        return match line.get(0..3).and_then(|code| code.parse::<u16>().ok()) {
+            Some(code) if code == expected => Ok(()),
+            _ => Err(Error::other(format!("Server replied: {}", line.trim()))),
+        };
+    }
+}
+
+
+impl Notifier for SmtpNotifier {
+    #[instrument(skip(event))]
+    fn notify(&self, event: &Event) -> Result<String, Mortal> {
+        self.deliver(event)
+            .map(|_| format!("Mail sent to: {}", self.to.join(", ")))
+            .map_err(|cause| failure("smtp", cause))
+    }
+}
+
+
+impl Notifier for CommandNotifier {
+    #[instrument(skip(event))]
+    fn notify(&self, event: &Event) -> Result<String, Mortal> {
+        let payload =
+            serde_json::to_string(event).map_err(|cause| failure("command", cause))?;
+        let mut child = Command::new(DEFAULT_SHELL)
+            .arg("-c")
+            .arg(&self.command)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|cause| failure("command", cause))?;
+        if let Some(mut stdin) = child.stdin.take() {
+            match stdin.write_all(payload.as_bytes()) {
+                // NOTE: command doesn't have to read the event:
+                Err(cause) if cause.kind() != ErrorKind::BrokenPipe => {
+                    return Err(failure("command", cause));
+                }
+                _ => (),
+            }
+        }
+        let output = child
+            .wait_with_output()
+            .map_err(|cause| failure("command", cause))?;
+        match output.status.success() {
+            true => Ok(format!("Notification passed to: {}", self.command)),
+            false => {
+                Err(failure(
+                    "command",
+                    format!(
+                        "{} exited with: {}. {}",
+                        self.command,
+                        output.status,
+                        String::from_utf8_lossy(&output.stderr).trim()
+                    ),
+                ))
+            }
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+
+    #[test]
+    fn header_is_single_line() {
+        assert_eq!(header("Service down"), "Service down");
+        assert_eq!(
+            header("Service down\r\nBcc: someone@example.com"),
+            "Service down Bcc: someone@example.com"
+        );
+        assert_eq!(header("multi\nline\n\nreason\n"), "multi line reason");
+    }
+
+
+    #[test]
+    fn smtp_reply_reads_multiline_replies() {
+        let mut reply = "250-mail.example.com\r\n250-SIZE 1000\r\n250 HELP\r\n".as_bytes();
+        assert!(smtp_reply(&mut reply, 250).is_ok());
+        assert!(reply.is_empty());
+        let mut reply = "354 End data with <CR><LF>.<CR><LF>\r\n".as_bytes();
+        assert!(smtp_reply(&mut reply, 354).is_ok());
+    }
+
+
+    #[test]
+    fn smtp_reply_fails_on_unexpected_code() {
+        let mut reply = "550 No such user\r\n".as_bytes();
+        assert_eq!(
+            smtp_reply(&mut reply, 250).unwrap_err().to_string(),
+            "Server replied: 550 No such user"
+        );
+        let mut reply = "250-mail.example.com\r\n".as_bytes();
+        assert_eq!(
+            smtp_reply(&mut reply, 250).unwrap_err().to_string(),
+            "Connection closed by server"
+        );
+        let mut reply = "hello\r\n".as_bytes();
+        assert!(smtp_reply(&mut reply, 250).is_err());
+    }
+}
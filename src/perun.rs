@@ -1,46 +1,292 @@
-use curl::easy::Easy;
+use chrono::{DateTime, NaiveDateTime, Utc};
+use curl::{
+    easy::{Easy, List},
+    Error as CurlError,
+};
+use curl_sys::{curl_certinfo, curl_easy_getinfo, CURLE_OK, CURLINFO_CERTINFO};
 use libc::kill;
 use regex::Regex;
 use std::{
-    io::prelude::*,
+    ffi::CStr,
+    io::{prelude::*, Error, ErrorKind},
+    net::{TcpStream, ToSocketAddrs},
     os::unix::net::UnixStream,
     path::Path,
     process::Command,
+    ptr,
     sync::{
         atomic::{AtomicUsize, Ordering},
         Arc,
     },
-    time::Duration,
+    time::{Duration, Instant},
 };
 
 use crate::{
     common::*,
     init_fields::InitFields,
-    mortal::Mortal::{self, *},
-    service::Service,
+    metrics::{self, CHECK_DURATION, DISK_FREE_BYTES, DISK_FREE_INODES, TLS_EXPIRY_DAYS},
+    mortal::{
+        Blessing::{self, *},
+        Mortal::{self, *},
+    },
+    service::{Service, UrlCheck},
     svarog::Svarog,
     *,
 };
 
 
+/// connects to first responding socket address resolved from given address
+fn tcp_connect(address: &str, timeout: Duration) -> Result<TcpStream, Error> {
+    let mut last_cause = Error::new(ErrorKind::NotFound, "address resolved to nothing");
+    for socket in address.to_socket_addrs()? {
+        match TcpStream::connect_timeout(&socket, timeout) {
+            Ok(stream) => return Ok(stream),
+            Err(cause) => last_cause = cause,
+        }
+    }
+    Err(last_cause)
+}
+
+
+/// returns regex matching expected response. Expected response is a regex if wrapped
+/// in slashes, literal text otherwise
+pub fn expectation(expected: &str) -> Result<Regex, regex::Error> {
+    let pattern = match expected
+        .strip_prefix('/')
+        .and_then(|rest| rest.strip_suffix('/'))
+    {
+        Some(pattern) => pattern.to_string(),
+        None => regex::escape(expected),
+    };
+    Regex::new(&pattern)
+}
+
+
+/// sends message through stream and reads response until it matches expected one.
+/// Returns read response and true if it matched (or nothing was expected)
+fn converse<S: Read + Write>(
+    stream: &mut S,
+    message: &str,
+    expected: &str,
+) -> Result<(String, bool), Error> {
+    if !message.is_empty() {
+        stream.write_all(message.as_bytes())?;
+        stream.flush()?;
+    }
+    if expected.is_empty() {
+        return Ok((String::new(), true));
+    }
+    let pattern = expectation(expected)
+        .map_err(|cause| Error::new(ErrorKind::InvalidInput, cause.to_string()))?;
+
+    let mut response = vec![];
+    let mut buffer = [0; 4096];
+    while response.len() < CHECKS_RESPONSE_MAX_SIZE {
+        match stream.read(&mut buffer) {
+            Ok(0) => break,
+            Ok(read) => {
+                response.extend_from_slice(&buffer[..read]);
+                if pattern.is_match(&String::from_utf8_lossy(&response)) {
+                    return Ok((String::from_utf8_lossy(&response).to_string(), true));
+                }
+            }
+            // NOTE: timeout after partial response means that response is unexpected:
+            Err(cause)
+                if !response.is_empty()
+                    && matches!(cause.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) =>
+            {
+                break
+            }
+            Err(cause) => return Err(cause),
+        }
+    }
+    Ok((String::from_utf8_lossy(&response).to_string(), false))
+}
+
+
+/// sets url, method, headers and body of request of URL check
+fn url_request(easy: &mut Easy, check: &UrlCheck) -> Result<(), CurlError> {
+    easy.url(&check.url)?;
+    if let Some(body) = &check.body {
+        easy.post(true)?;
+        easy.post_fields_copy(body.as_bytes())?;
+    }
+    if let Some(method) = &check.method {
+        match method.to_uppercase().as_str() {
+            "HEAD" => easy.nobody(true)?,
+            "GET" if check.body.is_none() => (),
+            method => easy.custom_request(method)?,
+        }
+    }
+    if let Some(headers) = &check.headers {
+        let mut list = List::new();
+        for header in headers {
+            list.append(header)?;
+        }
+        easy.http_headers(list)?;
+    }
+    Ok(())
+}
+
+
+/// sets TLS options of service on request of URL check
+fn tls_options(easy: &mut Easy, service: &Service) -> Result<(), CurlError> {
+    easy.ssl_verify_host(!service.insecure())?;
+    easy.ssl_verify_peer(!service.insecure())?;
+    let ca_file = service.ca_file();
+    if !ca_file.is_empty() {
+        easy.cainfo(Path::new(&ca_file))?;
+    }
+    let client_cert = service.client_cert();
+    if !client_cert.is_empty() {
+        easy.ssl_cert(Path::new(&client_cert))?;
+    }
+    let client_key = service.client_key();
+    if !client_key.is_empty() {
+        easy.ssl_key(Path::new(&client_key))?;
+    }
+    let resolve = service.resolve();
+    if !resolve.is_empty() {
+        let mut list = List::new();
+        for entry in resolve {
+            list.append(&entry)?;
+        }
+        easy.resolve(list)?;
+    }
+    Ok(())
+}
+
+
+/// returns reason of URL check failure - if response doesn't meet expectations of check
+fn url_assertion_failure(
+    check: &UrlCheck,
+    status: u32,
+    latency: u64,
+    headers: &[String],
+    body: &str,
+) -> Option<String> {
+    let status_accepted = match &check.expect_status {
+        Some(statuses) => statuses.contains(&status),
+        // NOTE: no status (0) means that no HTTP response was received:
+        None => (1..400).contains(&status),
+    };
+    if !status_accepted {
+        return Some(format!("Unexpected status: {}", status));
+    }
+    if let Some(max_latency) = check.max_latency {
+        if latency > max_latency {
+            return Some(format!("Latency exceeds: {} ms", max_latency));
+        }
+    }
+    for expected in check.expect_headers.iter().flatten() {
+        let (name, value) = match expected.split_once(':') {
+            Some((name, value)) => (name.trim(), Some(value.trim())),
+            None => (expected.trim(), None),
+        };
+        let present = headers.iter().any(|header| {
+            match header.split_once(':') {
+                Some((header_name, header_value)) => {
+                    header_name.trim().eq_ignore_ascii_case(name)
+                        && match value {
+                            Some(value) => header_value.trim() == value,
+                            None => true,
+                        }
+                }
+                None => false,
+            }
+        });
+        if !present {
+            return Some(format!("Missing response header: {}", expected));
+        }
+    }
+    if let Some(expected) = &check.expect_body {
+        match expectation(expected) {
+            Ok(pattern) if pattern.is_match(body) => (),
+            Ok(_) => return Some(format!("Response body doesn't match: {}", expected)),
+            Err(cause) => {
+                return Some(format!(
+                    "Invalid body expectation: {}. Reason: {}",
+                    expected, cause
+                ));
+            }
+        }
+    }
+    None
+}
+
+
+/// returns days left until expiry of the earliest expiring certificate of peer chain,
+/// and its expire date. Certificates info has to be enabled for performed request
+fn certificates_expiry(easy: &Easy) -> Option<(i64, String)> {
+    let mut expire_dates = vec![];
+    unsafe {
+        let mut info: *mut curl_certinfo = ptr::null_mut();
+        if curl_easy_getinfo(easy.raw(), CURLINFO_CERTINFO, &mut info) != CURLE_OK
+            || info.is_null()
+        {
+            return None;
+        }
+        for index in 0..(*info).num_of_certs.max(0) as usize {
+            let mut entry = *(*info).certinfo.add(index);
+            while !entry.is_null() {
+                let data = CStr::from_ptr((*entry).data).to_string_lossy();
+                if let Some(date) = data.strip_prefix("Expire date:") {
+                    expire_dates.push(date.trim().to_string());
+                }
+                entry = (*entry).next;
+            }
+        }
+    }
+    expire_dates
+        .into_iter()
+        .filter_map(|date| days_until(&date, Utc::now()).map(|days| (days, date)))
+        .min_by_key(|(days, _)| *days)
+}
+
+
+/// returns days left from now until given certificate expire date
+fn days_until(date: &str, now: DateTime<Utc>) -> Option<i64> {
+    // NOTE: dates are in form like: "Mar  5 12:00:00 2027 GMT"
+    let normalized = date
+        .trim()
+        .trim_end_matches("GMT")
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ");
+    match NaiveDateTime::parse_from_str(&normalized, "%b %d %H:%M:%S %Y") {
+        Ok(expires) => Some((expires.and_utc() - now).num_days()),
+        Err(cause) => {
+            warn!(
+                "Can't parse certificate expire date: {}. Reason: {}",
+                date, cause
+            );
+            None
+        }
+    }
+}
+
+
 // Perun is a supervisor deity
 //
 pub trait Perun {
-    fn try_pid_file(&self) -> Result<Mortal, Mortal>;
-    fn try_unix_socket(&self) -> Result<Mortal, Mortal>;
-    fn try_urls(&self) -> Result<Mortal, Mortal>;
-    fn try_disk_check(&self) -> Result<Mortal, Mortal>;
+    fn try_pid_file(&self) -> Result<Blessing, Mortal>;
+    fn try_unix_socket(&self) -> Result<Blessing, Mortal>;
+    fn try_tcp(&self) -> Result<Blessing, Mortal>;
+    fn try_urls(&self, warnings: &mut Vec<Mortal>) -> Result<Blessing, Mortal>;
+    fn try_disk_check(&self) -> Result<Blessing, Mortal>;
 
-    fn checks_for(&self) -> Result<Mortal, Mortal>;
+    fn checks_for(&self) -> Result<Blessing, Mortal>;
+    fn checks_with_warnings(&self) -> (Result<Blessing, Mortal>, Vec<Mortal>);
+    fn perform_checks(&self, warnings: &mut Vec<Mortal>) -> Result<Blessing, Mortal>;
     fn check_disk_space(&self) -> (i64, i64);
 }
 
 
 impl Perun for Service {
     #[instrument]
-    fn try_urls(&self) -> Result<Mortal, Mortal> {
-        for url in self.urls() {
-            // let mut dst = Vec::new();
+    fn try_urls(&self, warnings: &mut Vec<Mortal>) -> Result<Blessing, Mortal> {
+        let warn_days = self.tls_expiry_warn_days();
+        for check in self.urls() {
             let mut easy = Easy::new();
             easy.connect_timeout(Duration::from_millis(self.clone().checks_url_timeout()))
                 .unwrap();
@@ -50,50 +296,118 @@ impl Perun for Service {
                 .unwrap();
             easy.tcp_nodelay(true).unwrap();
             easy.follow_location(true).unwrap();
-            easy.ssl_verify_host(true).unwrap();
-            easy.ssl_verify_peer(true).unwrap();
-            easy.cainfo(Path::new(CACERT_PEM)).unwrap();
-            match easy.url(url.as_ref()) {
-                Ok(_) => {
-                    match easy.perform() {
-                        Ok(_) => trace!("Done request to: {} for: {}", url, self.styled()),
-                        Err(cause) => {
-                            return Err(CheckURL {
-                                service: self.clone(),
-                                url,
-                                cause,
-                            });
-                        }
-                    }
+            if let Err(cause) = url_request(&mut easy, &check)
+                .and_then(|_| tls_options(&mut easy, self))
+                .and_then(|_| easy.certinfo(warn_days > 0))
+            {
+                return Err(CheckURLFail {
+                    service: Box::new(self.clone()),
+                    cause,
+                });
+            }
+
+            let mut body = vec![];
+            let mut headers = vec![];
+            let started = Instant::now();
+            let performed = {
+                let mut transfer = easy.transfer();
+                transfer
+                    .write_function(|data| {
+                        let room = CHECKS_RESPONSE_MAX_SIZE.saturating_sub(body.len());
+                        body.extend_from_slice(&data[..data.len().min(room)]);
+                        Ok(data.len())
+                    })
+                    .and_then(|_| {
+                        transfer.header_function(|line| {
+                            let line = String::from_utf8_lossy(line).trim().to_string();
+                            // NOTE: redirects are followed, keep headers of last response:
+                            if line.starts_with("HTTP/") {
+                                headers.clear();
+                            }
+                            headers.push(line);
+                            true
+                        })
+                    })
+                    .and_then(|_| transfer.perform())
+            };
+            let elapsed = started.elapsed();
+            metrics::observe(
+                CHECK_DURATION,
+                &[("service", &self.name()), ("check", "url"), ("target", &check.url)],
+                elapsed.as_secs_f64(),
+            );
+            if let Err(cause) = performed {
+                return Err(CheckURL {
+                    service: Box::new(self.clone()),
+                    url: check.url,
+                    cause,
+                });
+            }
+
+            let status = easy.response_code().unwrap_or_default();
+            let latency = easy.total_time().unwrap_or(elapsed).as_millis() as u64;
+            let body = String::from_utf8_lossy(&body);
+            match url_assertion_failure(&check, status, latency, &headers, &body) {
+                Some(reason) => {
+                    return Err(CheckURLAssertion {
+                        service: Box::new(self.clone()),
+                        url: check.url,
+                        status,
+                        latency,
+                        reason,
+                    });
                 }
-                Err(cause) => {
-                    return Err(CheckURLFail {
-                        service: self.clone(),
-                        cause,
+                None => {
+                    trace!(
+                        "Done request to: {} for: {} (status: {}, latency: {} ms)",
+                        check,
+                        self.styled(),
+                        status,
+                        latency
+                    )
+                }
+            }
+
+            if warn_days == 0 {
+                continue;
+            }
+            if let Some((days, expires)) = certificates_expiry(&easy) {
+                metrics::gauge(
+                    TLS_EXPIRY_DAYS,
+                    &[("service", &self.name()), ("url", &check.url)],
+                    days as f64,
+                );
+                // NOTE: expiring certificate is not a failure - it's reported aside of result:
+                if days < warn_days as i64 {
+                    warnings.push(CheckTlsExpiry {
+                        service: Box::new(self.clone()),
+                        url: check.url,
+                        days,
+                        expires,
                     });
                 }
             }
         }
         Ok(OkUrlsChecks {
-            service: self.clone(),
+            service: Box::new(self.clone()),
         })
     }
 
 
     #[instrument]
-    fn try_pid_file(&self) -> Result<Mortal, Mortal> {
+    fn try_pid_file(&self) -> Result<Blessing, Mortal> {
         match self.read_pid() {
             Ok(pid) => unsafe {
                 match kill(pid, 0) {
                     0 => {
                         Ok(OkPidAlive {
-                            service: self.clone(),
+                            service: Box::new(self.clone()),
                             pid,
                         })
                     }
                     _ => {
                         Err(CheckPidfileMalformed {
-                            service: self.clone(),
+                            service: Box::new(self.clone()),
                         })
                     }
                 }
@@ -104,52 +418,135 @@ impl Perun for Service {
 
 
     #[instrument]
-    fn try_unix_socket(&self) -> Result<Mortal, Mortal> {
+    fn try_unix_socket(&self) -> Result<Blessing, Mortal> {
         let path = self.clone().unix_socket();
-        match UnixStream::connect(path) {
+        let timeout = Some(Duration::from_millis(self.checks_response_timeout()));
+        let started = Instant::now();
+        let result = match UnixStream::connect(&path) {
             Ok(mut stream) => {
-                match stream.write_all(UNIX_SOCKET_MSG) {
+                let conversation = stream
+                    .set_read_timeout(timeout)
+                    .and_then(|_| stream.set_write_timeout(timeout))
+                    .and_then(|_| {
+                        converse(
+                            &mut stream,
+                            &self.unix_socket_send(),
+                            &self.unix_socket_expect(),
+                        )
+                    });
+                match conversation {
+                    Ok((_, true)) => {
+                        Ok(OkUnixSockCheck {
+                            service: Box::new(self.clone()),
+                        })
+                    }
+                    Ok((response, false)) => {
+                        Err(CheckUnexpectedResponse {
+                            service: Box::new(self.clone()),
+                            check: "unix_socket",
+                            target: path.clone(),
+                            expected: self.unix_socket_expect(),
+                            response,
+                        })
+                    }
                     Err(cause) => {
                         Err(CheckUnixSocket {
-                            service: self.clone(),
+                            service: Box::new(self.clone()),
                             cause,
                         })
                     }
-                    Ok(_) => {
-                        // let mut response = String::new();
-                        // stream.read_to_string(&mut response).unwrap();
-                        Ok(OkUnixSockCheck {
-                            service: self.clone(),
-                        })
-                    }
                 }
             }
             Err(cause) => {
                 Err(CheckUnixSocketMissing {
-                    service: self.clone(),
+                    service: Box::new(self.clone()),
                     cause,
                 })
             }
+        };
+        metrics::observe(
+            CHECK_DURATION,
+            &[
+                ("service", &self.name()),
+                ("check", "unix_socket"),
+                ("target", &path),
+            ],
+            started.elapsed().as_secs_f64(),
+        );
+        result
+    }
+
+
+    #[instrument]
+    fn try_tcp(&self) -> Result<Blessing, Mortal> {
+        let timeout = Duration::from_millis(self.checks_tcp_timeout());
+        let response_timeout = Some(Duration::from_millis(self.checks_response_timeout()));
+        for address in self.tcp() {
+            let started = Instant::now();
+            let conversation = tcp_connect(&address, timeout).and_then(|mut stream| {
+                stream.set_read_timeout(response_timeout)?;
+                stream.set_write_timeout(response_timeout)?;
+                converse(&mut stream, &self.tcp_send(), &self.tcp_expect())
+            });
+            metrics::observe(
+                CHECK_DURATION,
+                &[("service", &self.name()), ("check", "tcp"), ("target", &address)],
+                started.elapsed().as_secs_f64(),
+            );
+            match conversation {
+                Ok((_, true)) => trace!("Connected to: {} for: {}", address, self.styled()),
+                Ok((response, false)) => {
+                    return Err(CheckUnexpectedResponse {
+                        service: Box::new(self.clone()),
+                        check: "tcp",
+                        target: address,
+                        expected: self.tcp_expect(),
+                        response,
+                    });
+                }
+                Err(cause) => {
+                    return Err(CheckTcp {
+                        service: Box::new(self.clone()),
+                        address,
+                        cause,
+                    });
+                }
+            }
         }
+        Ok(OkTcpChecks {
+            service: Box::new(self.clone()),
+        })
     }
 
 
     #[instrument]
-    fn try_disk_check(&self) -> Result<Mortal, Mortal> {
-        match self.check_disk_space() {
+    fn try_disk_check(&self) -> Result<Blessing, Mortal> {
+        let (space, inodes) = self.check_disk_space();
+        if space >= 0 {
+            metrics::gauge(
+                DISK_FREE_BYTES,
+                &[("service", &self.name())],
+                (space * 1024) as f64,
+            );
+        }
+        // NOTE: inodes aren't checked under Linux:
+        if cfg!(not(target_os = "linux")) && inodes >= 0 {
+            metrics::gauge(DISK_FREE_INODES, &[("service", &self.name())], inodes as f64);
+        }
+        match (space, inodes) {
             (space, _) if space / 1024 < self.clone().disk_minimum_space() => {
                 Err(CheckDiskSpace {
-                    service: self.clone(),
+                    service: Box::new(self.clone()),
                 })
             }
             (_, inodes) if inodes < self.clone().disk_minimum_inodes() => {
                 Err(CheckDiskInodes {
-                    service: self.clone(),
+                    service: Box::new(self.clone()),
                 })
             }
             (_space, _inodes) => {
                 Ok(OkDiskCheck {
-                    service: self.clone(),
+                    service: Box::new(self.clone()),
                 })
             }
         }
@@ -186,7 +583,7 @@ impl Perun for Service {
     //
 
     #[instrument]
-    #[cfg(all(target_os = "linux"))]
+    #[cfg(target_os = "linux")]
     fn check_disk_space(&self) -> (i64, i64) {
         lazy_static! {
             static ref FIRST_LINE: Regex = Regex::new(r"^.*\n").unwrap();
@@ -298,7 +695,31 @@ impl Perun for Service {
 
 
     #[instrument]
-    fn checks_for(&self) -> Result<Mortal, Mortal> {
+    fn checks_for(&self) -> Result<Blessing, Mortal> {
+        let (result, warnings) = self.checks_with_warnings();
+        for warning in warnings {
+            warn!("{}", warning);
+        }
+        result
+    }
+
+
+    #[instrument]
+    fn checks_with_warnings(&self) -> (Result<Blessing, Mortal>, Vec<Mortal>) {
+        let mut warnings = vec![];
+        let started = Instant::now();
+        let result = self.perform_checks(&mut warnings);
+        metrics::observe(
+            CHECK_DURATION,
+            &[("service", &self.name()), ("check", "all"), ("target", "")],
+            started.elapsed().as_secs_f64(),
+        );
+        (result, warnings)
+    }
+
+
+    #[instrument]
+    fn perform_checks(&self, warnings: &mut Vec<Mortal>) -> Result<Blessing, Mortal> {
         let checks_performed = Arc::new(AtomicUsize::new(0));
 
         match self.try_disk_check() {
@@ -327,6 +748,22 @@ impl Perun for Service {
             }
         }
 
+        if !self.tcp().is_empty() {
+            match self.try_tcp() {
+                Ok(_) => {
+                    checks_performed.fetch_add(1, Ordering::SeqCst);
+                    debug!(
+                        "TCP check passed for: {}, with tcp: {:?}",
+                        self.styled(),
+                        self.tcp()
+                    )
+                }
+                Err(err) => return Err(err),
+            }
+        } else {
+            trace!("Undefined tcp for: {}", self.styled())
+        }
+
         match self.pid_file().as_ref() {
             "" => trace!("Undefined pid_file for: {}", self.styled()),
             _ => {
@@ -345,13 +782,16 @@ impl Perun for Service {
         }
 
         if !self.urls().is_empty() {
-            match self.try_urls() {
+            match self.try_urls(warnings) {
                 Ok(_) => {
                     checks_performed.fetch_add(1, Ordering::SeqCst);
                     debug!(
                         "URLs check passed for: {}, with urls: {:?}",
                         self.styled(),
                         self.urls()
+                            .iter()
+                            .map(|check| check.url.as_str())
+                            .collect::<Vec<&str>>()
                     )
                 }
                 Err(err) => return Err(err),
@@ -368,15 +808,117 @@ impl Perun for Service {
         match checks_performed.load(Ordering::SeqCst) {
             0 => {
                 Err(CheckNoServiceChecks {
-                    service: self.clone(),
+                    service: Box::new(self.clone()),
                 })
             }
             _ => {
                 Ok(OkAllChecks {
-                    service: self.clone(),
+                    service: Box::new(self.clone()),
                     amount: checks_performed.load(Ordering::SeqCst) as i32,
                 })
             }
         }
     }
 }
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+
+    fn check() -> UrlCheck {
+        UrlCheck {
+            url: "http://localhost/".to_string(),
+            ..UrlCheck::default()
+        }
+    }
+
+
+    #[test]
+    fn url_assertion_accepts_statuses() {
+        assert_eq!(url_assertion_failure(&check(), 200, 0, &[], ""), None);
+        assert_eq!(url_assertion_failure(&check(), 302, 0, &[], ""), None);
+        assert!(url_assertion_failure(&check(), 404, 0, &[], "").is_some());
+        assert!(url_assertion_failure(&check(), 0, 0, &[], "").is_some());
+
+        let check = UrlCheck {
+            expect_status: Some(vec![404]),
+            ..check()
+        };
+        assert_eq!(url_assertion_failure(&check, 404, 0, &[], ""), None);
+        assert!(url_assertion_failure(&check, 200, 0, &[], "").is_some());
+    }
+
+
+    #[test]
+    fn url_assertion_matches_headers() {
+        let headers = [
+            "HTTP/1.1 200 OK".to_string(),
+            "Content-Type: application/json".to_string(),
+            "X-Version: 1.2".to_string(),
+        ];
+        let expecting = |expected: &str| {
+            UrlCheck {
+                expect_headers: Some(vec![expected.to_string()]),
+                ..check()
+            }
+        };
+        let failure = |expected| {
+            url_assertion_failure(&expecting(expected), 200, 0, &headers, "")
+        };
+        assert_eq!(failure("x-version"), None);
+        assert_eq!(failure("Content-Type: application/json"), None);
+        assert_eq!(failure("X-Version:1.2"), None);
+        assert!(failure("X-Version: 2.0").is_some());
+        assert!(failure("X-Missing").is_some());
+    }
+
+
+    #[test]
+    fn url_assertion_limits_latency() {
+        let check = UrlCheck {
+            max_latency: Some(100),
+            ..check()
+        };
+        assert_eq!(url_assertion_failure(&check, 200, 100, &[], ""), None);
+        assert!(url_assertion_failure(&check, 200, 101, &[], "").is_some());
+    }
+
+
+    #[test]
+    fn url_assertion_matches_body() {
+        let expecting = |expected: &str| {
+            UrlCheck {
+                expect_body: Some(expected.to_string()),
+                ..check()
+            }
+        };
+        let body = r#"{"status": "ok", "version": "1.2"}"#;
+        let failure = |expected| {
+            url_assertion_failure(&expecting(expected), 200, 0, &[], body)
+        };
+        assert_eq!(failure("\"status\": \"ok\""), None);
+        assert_eq!(failure("/version.: .1\\.2/"), None);
+        assert!(failure("1.2.").is_some());
+        assert!(failure("/failed/").is_some());
+    }
+
+
+    #[test]
+    fn days_until_parses_openssl_dates() {
+        let now = Utc.with_ymd_and_hms(2027, 3, 1, 12, 0, 0).unwrap();
+        assert_eq!(days_until("Mar  5 12:00:00 2027 GMT", now), Some(4));
+        assert_eq!(days_until(" Mar 15 11:00:00 2027 GMT ", now), Some(13));
+        assert_eq!(days_until("Feb 27 12:00:00 2027 GMT", now), Some(-2));
+    }
+
+
+    #[test]
+    fn days_until_rejects_unknown_dates() {
+        let now = Utc.with_ymd_and_hms(2027, 3, 1, 12, 0, 0).unwrap();
+        assert_eq!(days_until("2027-03-05T12:00:00Z", now), None);
+        assert_eq!(days_until("", now), None);
+    }
+}
@@ -0,0 +1,253 @@
+use std::{
+    collections::HashMap,
+    fs::{copy, rename, File, OpenOptions},
+    io::{prelude::*, BufReader, Error, SeekFrom},
+    sync::{Arc, Mutex},
+};
+
+use crate::*;
+
+
+/// amount of bytes read from the end of file to find last lines
+static TAIL_CHUNK: u64 = 65536;
+
+
+lazy_static! {
+    /// rotating files shared by all writers - by path
+    static ref OPENED: Mutex<HashMap<String, Arc<Mutex<RotatingFile>>>> =
+        Mutex::new(HashMap::new());
+}
+
+
+// RotatingFile is an append only file, rotated when it grows over max_size.
+// Rotated files are kept as: file.1 (newest) .. file.N (oldest).
+// Current file is copied and truncated (never renamed), so other processes
+// appending to it directly (like daemons) keep writing to the current file.
+//
+
+#[derive(Debug)]
+pub struct RotatingFile {
+    /// path to current file
+    path: String,
+
+    /// maximum size of file in bytes
+    max_size: u64,
+
+    /// amount of rotated files to keep
+    keep: usize,
+
+    /// current file handle
+    file: File,
+
+    /// current file size
+    written: u64,
+}
+
+
+impl RotatingFile {
+    /// opens file for appending
+    #[instrument]
+    pub fn open(path: &str, max_size: u64, keep: usize) -> Result<RotatingFile, Error> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        let written = file.metadata()?.len();
+        Ok(RotatingFile {
+            path: path.to_string(),
+            max_size,
+            keep,
+            file,
+            written,
+        })
+    }
+
+
+    /// returns rotating file shared with other writers of the same path.
+    /// Limits of already opened file are updated with given ones
+    #[instrument]
+    pub fn shared(
+        path: &str,
+        max_size: u64,
+        keep: usize,
+    ) -> Result<Arc<Mutex<RotatingFile>>, Error> {
+        let mut opened = OPENED
+            .lock()
+            .map_err(|cause| Error::other(cause.to_string()))?;
+        match opened.get(path) {
+            Some(file) => {
+                match file.lock() {
+                    Ok(mut current) => {
+                        current.max_size = max_size;
+                        current.keep = keep;
+                    }
+                    Err(cause) => return Err(Error::other(cause.to_string())),
+                }
+                Ok(file.clone())
+            }
+            None => {
+                let file = Arc::new(Mutex::new(RotatingFile::open(path, max_size, keep)?));
+                opened.insert(path.to_string(), file.clone());
+                Ok(file)
+            }
+        }
+    }
+
+
+    /// moves file.N-1 to file.N, .., copies file to file.1 and truncates it
+    #[instrument]
+    pub fn rotate(&mut self) -> Result<(), Error> {
+        self.file.flush()?;
+        if self.keep > 0 {
+            for index in (1..self.keep).rev() {
+                let from = format!("{}.{}", self.path, index);
+                let to = format!("{}.{}", self.path, index + 1);
+                match rename(&from, &to) {
+                    Ok(_) => trace!("Rotated: {} to: {}", from, to),
+                    Err(_) => trace!("No: {} to rotate", from),
+                }
+            }
+            copy(&self.path, format!("{}.1", self.path))?;
+        }
+        self.file.set_len(0)?;
+        self.written = 0;
+        debug!("Rotated file: {}", self.path);
+        Ok(())
+    }
+
+
+    /// rotates file if it grew over max_size - also by writes of other processes.
+    /// Returns true if file was rotated
+    #[instrument]
+    pub fn rotate_oversized(&mut self) -> Result<bool, Error> {
+        self.written = self.file.metadata()?.len();
+        match self.written > self.max_size {
+            true => self.rotate().map(|_| true),
+            false => Ok(false),
+        }
+    }
+}
+
+
+impl Write for RotatingFile {
+    fn write(&mut self, buf: &[u8]) -> Result<usize, Error> {
+        if self.written > 0 && self.written + buf.len() as u64 > self.max_size {
+            self.rotate()?;
+        }
+        let written = self.file.write(buf)?;
+        self.written += written as u64;
+        Ok(written)
+    }
+
+
+    fn flush(&mut self) -> Result<(), Error> {
+        self.file.flush()
+    }
+}
+
+
+/// returns last lines of given file
+#[instrument]
+pub fn tail_lines(path: &str, amount: usize) -> Vec<String> {
+    let mut raw = vec![];
+    match File::open(path) {
+        Ok(mut file) => {
+            let size = file.metadata().map(|meta| meta.len()).unwrap_or(0);
+            match file
+                .seek(SeekFrom::Start(size.saturating_sub(TAIL_CHUNK)))
+                .and_then(|_| file.read_to_end(&mut raw))
+            {
+                Ok(read) => trace!("Read: {} bytes of: {}", read, path),
+                Err(cause) => debug!("Can't read: {}. Reason: {}", path, cause),
+            }
+        }
+        Err(cause) => debug!("Can't open: {}. Reason: {}", path, cause),
+    }
+    let buffer = String::from_utf8_lossy(&raw);
+    let lines: Vec<String> = buffer.lines().map(|line| line.to_string()).collect();
+    lines[lines.len().saturating_sub(amount)..].to_vec()
+}
+
+
+/// copies lines from reader to shared rotating file, until reader is closed
+pub fn copy_lines<R: Read>(reader: R, sink: Arc<Mutex<RotatingFile>>) {
+    for line in BufReader::new(reader).split(b'\n') {
+        match line {
+            Ok(mut line) => {
+                line.push(b'\n');
+                match sink.lock() {
+                    Ok(mut file) => {
+                        if let Err(cause) = file.write_all(&line).and_then(|_| file.flush()) {
+                            error!("Failed to write output line! Reason: {}", cause);
+                        }
+                    }
+                    Err(cause) => error!("Output file lock poisoned! Reason: {}", cause),
+                }
+            }
+            Err(cause) => {
+                debug!("Output closed. Reason: {}", cause);
+                break;
+            }
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{env, fs::remove_file, process};
+
+
+    #[test]
+    fn shared_file_takes_new_limits() {
+        let path = format!(
+            "{}/deities-rotating-{}.log",
+            env::temp_dir().display(),
+            process::id()
+        );
+        let file = RotatingFile::shared(&path, 1024, 1).unwrap();
+        RotatingFile::shared(&path, 2048, 3).unwrap();
+        {
+            let file = file.lock().unwrap();
+            assert_eq!(file.max_size, 2048);
+            assert_eq!(file.keep, 3);
+        }
+        remove_file(&path).unwrap();
+    }
+
+
+    #[test]
+    fn rotates_over_max_size() {
+        let path = format!(
+            "{}/deities-rotate-{}.log",
+            env::temp_dir().display(),
+            process::id()
+        );
+        let mut file = RotatingFile::open(&path, 8, 1).unwrap();
+        file.write_all(b"first\n").unwrap();
+        file.write_all(b"second\n").unwrap();
+        assert_eq!(tail_lines(&path, 5), vec!["second"]);
+        assert_eq!(tail_lines(&format!("{}.1", path), 5), vec!["first"]);
+        remove_file(&path).unwrap();
+        remove_file(format!("{}.1", path)).unwrap();
+    }
+
+
+    #[test]
+    fn rotates_file_written_by_others() {
+        let path = format!(
+            "{}/deities-rotate-others-{}.log",
+            env::temp_dir().display(),
+            process::id()
+        );
+        let mut file = RotatingFile::open(&path, 8, 1).unwrap();
+        let mut daemon = OpenOptions::new().append(true).open(&path).unwrap();
+        daemon.write_all(b"daemon\n").unwrap();
+        assert!(!file.rotate_oversized().unwrap());
+        daemon.write_all(b"output\n").unwrap();
+        assert!(file.rotate_oversized().unwrap());
+        daemon.write_all(b"after\n").unwrap();
+        assert_eq!(tail_lines(&path, 5), vec!["after"]);
+        assert_eq!(tail_lines(&format!("{}.1", path), 5), vec!["daemon", "output"]);
+        remove_file(&path).unwrap();
+        remove_file(format!("{}.1", path)).unwrap();
+    }
+}
@@ -0,0 +1,364 @@
+use glob::Pattern;
+use notify::{recommended_watcher, Event, RecommendedWatcher, RecursiveMode, Watcher};
+use std::{
+    collections::HashMap,
+    path::Path,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    thread::{sleep, Builder},
+    time::{Duration, Instant},
+};
+
+use crate::{
+    context::OnShutdown,
+    init_fields::InitFields,
+    service::{Service, RESTART_KEYS},
+    veles::Veles,
+    *,
+};
+
+
+/// longest pause between scheduler ticks - so definition changes are noticed quickly
+static SCHEDULER_TICK: Duration = Duration::from_millis(1000);
+
+
+/// how often to check if runs in progress are finished - on shutdown
+static SHUTDOWN_POLL: Duration = Duration::from_millis(100);
+
+
+/// supervision task run by scheduler for single service
+pub type Task = fn(Context, Service);
+
+
+/// clears running flag of job, also when its task panics
+struct RunningGuard(Arc<AtomicBool>);
+
+
+impl Drop for RunningGuard {
+    fn drop(&mut self) {
+        self.0.store(false, Ordering::SeqCst)
+    }
+}
+
+
+/// schedule of single service definition
+#[derive(Debug, Clone)]
+pub struct Job {
+    /// last known-good service definition
+    pub service: Service,
+
+    /// raw content of last known-good service definition
+    pub definition: String,
+
+    /// previous definition of service, if it has to be restarted with current one
+    pub restart: Option<Service>,
+
+    /// time of next run
+    pub next_run: Instant,
+
+    /// true while task of previous run is still in progress
+    pub running: Arc<AtomicBool>,
+}
+
+
+// Definitions are last known-good service definitions loaded by Scheduler - in start order.
+// They're shared by all clones of Context, so services are resolved without reading
+// (possibly broken) definition files again.
+//
+
+#[derive(Debug, Clone, Default)]
+pub struct Definitions(Arc<Mutex<Vec<Service>>>);
+
+
+impl Definitions {
+    /// replaces known definitions with given ones
+    pub fn set(&self, services: Vec<Service>) {
+        match self.0.lock() {
+            Ok(mut definitions) => *definitions = services,
+            Err(cause) => error!("Definitions lock poisoned! Reason: {}", cause),
+        }
+    }
+
+
+    /// returns all known definitions
+    pub fn get(&self) -> Vec<Service> {
+        match self.0.lock() {
+            Ok(definitions) => definitions.clone(),
+            Err(_) => vec![],
+        }
+    }
+
+
+    /// returns known definition of service with given name (or definition file name)
+    pub fn find(&self, name: &str) -> Option<Service> {
+        self.get()
+            .into_iter()
+            .find(|service| service.name() == name || service.ini_file() == name)
+    }
+}
+
+
+// Scheduler keeps independent timer of each service definition.
+// Each run is performed in own thread, so slow service doesn't delay others.
+// Run is skipped if previous one of the same service is still in progress.
+// Definitions are loaded once, and reloaded on demand or when their files change.
+//
+
+pub struct Scheduler {
+    context: Context,
+    task: Task,
+
+    /// jobs by definition file name
+    jobs: HashMap<String, Job>,
+
+    /// set when definitions have to be reloaded
+    changed: Arc<AtomicBool>,
+
+    /// watcher of services dir. Without it definitions are reloaded only on demand
+    watcher: Option<RecommendedWatcher>,
+}
+
+
+impl Scheduler {
+    /// creates scheduler performing given task on services of context
+    pub fn new(context: Context, task: Task) -> Scheduler {
+        Scheduler {
+            context,
+            task,
+            jobs: HashMap::new(),
+            changed: Arc::new(AtomicBool::new(true)),
+            watcher: None,
+        }
+    }
+
+
+    /// starts watching services dir for changes of definitions
+    #[instrument(skip(self))]
+    pub fn watch(&mut self) -> Result<(), notify::Error> {
+        let changed = self.changed.clone();
+        let pattern = Pattern::new(&self.context.services_glob).ok();
+        let mut watcher = recommended_watcher(move |event: notify::Result<Event>| {
+            match event {
+                Ok(event) => {
+                    let definition_changed = event.paths.iter().any(|path| {
+                        match (&pattern, path.file_name().and_then(|name| name.to_str())) {
+                            (Some(pattern), Some(file_name)) => pattern.matches(file_name),
+                            _ => true,
+                        }
+                    });
+                    if definition_changed {
+                        changed.store(true, Ordering::SeqCst)
+                    }
+                }
+                Err(cause) => error!("Services dir watch failure! Reason: {}", cause),
+            }
+        })?;
+        watcher.watch(
+            Path::new(&self.context.services_dir),
+            RecursiveMode::NonRecursive,
+        )?;
+        info!("Watching for definition changes in: {}", self.context.services_dir);
+        self.watcher = Some(watcher);
+        Ok(())
+    }
+
+
+    /// returns last known-good definitions of all scheduled services - in start order
+    pub fn services(&self) -> Vec<Service> {
+        let mut file_names: Vec<&String> = self.jobs.keys().collect();
+        file_names.sort();
+        Service::start_order(
+            file_names
+                .into_iter()
+                .filter_map(|file_name| self.jobs.get(file_name))
+                .map(|job| job.service.clone())
+                .collect(),
+        )
+    }
+
+
+    /// loads new and changed definitions, forgets removed ones.
+    /// Broken definition doesn't replace last known-good one
+    #[instrument(skip(self))]
+    pub fn reload(&mut self) {
+        self.changed.store(false, Ordering::SeqCst);
+        let now = Instant::now();
+        let file_names: Vec<String> = self
+            .context
+            .list_services()
+            .iter()
+            .filter_map(|path| path.file_name().and_then(|name| name.to_str()))
+            .map(|name| name.to_string())
+            .collect();
+
+        self.jobs.retain(|file_name, _| {
+            let present = file_names.contains(file_name);
+            if !present {
+                info!("Service definition removed: {}", file_name);
+            }
+            present
+        });
+
+        for file_name in file_names {
+            let path = format!("{}/{}", self.context.services_dir, file_name);
+            let loaded = Service::load_definition(path).and_then(|definition| {
+                Service::parse(&self.context, file_name.clone(), &definition)
+                    .map(|service| (service, definition))
+            });
+            match (self.jobs.get_mut(&file_name), loaded) {
+                (None, Ok((service, definition))) => {
+                    debug!("Scheduling service: {}", service);
+                    self.jobs.insert(
+                        file_name,
+                        Job {
+                            next_run: now + Duration::from_millis(service.checks_interval()),
+                            service,
+                            definition,
+                            restart: None,
+                            running: Arc::new(AtomicBool::new(false)),
+                        },
+                    );
+                }
+                (None, Err(reason)) => error!("Definition load failure: {}", reason),
+                (Some(job), Err(reason)) => {
+                    error!(
+                        "Definition load failure: {}. Keeping last known-good definition \
+                         of: {}",
+                        reason, job.service
+                    )
+                }
+                (Some(job), Ok((service, definition))) => {
+                    let changed = Service::changed_keys(&job.definition, &definition);
+                    if changed.is_empty() {
+                        continue;
+                    }
+                    info!("Definition of: {} changed: {}", service, changed.join(", "));
+                    if changed.iter().any(|key| RESTART_KEYS.contains(&key.as_str())) {
+                        // NOTE: keep the oldest definition, it's the one still running:
+                        if job.restart.is_none() {
+                            job.restart = Some(job.service.clone());
+                        }
+                        job.next_run = now;
+                    } else {
+                        job.next_run = job
+                            .next_run
+                            .min(now + Duration::from_millis(service.checks_interval()));
+                    }
+                    job.service = service;
+                    job.definition = definition;
+                }
+            }
+        }
+        self.context.definitions.set(self.services());
+    }
+
+
+    /// reloads definitions if they changed and runs due jobs.
+    /// Returns time to wait before next tick
+    #[instrument(skip(self))]
+    pub fn tick(&mut self) -> Duration {
+        if self.changed.load(Ordering::SeqCst) {
+            self.reload();
+        }
+        let now = Instant::now();
+        // NOTE: due jobs are run in start order - so dependencies are checked first:
+        let file_names: Vec<String> = self
+            .services()
+            .iter()
+            .map(|service| service.ini_file())
+            .collect();
+        for file_name in file_names {
+            let job = match self.jobs.get_mut(&file_name) {
+                Some(job) if job.next_run <= now => job,
+                _ => continue,
+            };
+            job.next_run = now + Duration::from_millis(job.service.checks_interval());
+            if job.running.swap(true, Ordering::SeqCst) {
+                warn!(
+                    "Previous run of: {} still in progress. Skipping this one.",
+                    job.service
+                );
+                continue;
+            }
+            let guard = RunningGuard(job.running.clone());
+            let context = self.context.clone();
+            let service = job.service.clone();
+            let previous = job.restart.take();
+            let task = self.task;
+            if let Err(cause) = Builder::new().name(service.name()).spawn(move || {
+                let _guard = guard;
+                match previous {
+                    Some(previous) => Scheduler::restart(&context, &previous, &service),
+                    None => task(context, service),
+                }
+            }) {
+                error!("Failed to spawn supervision thread! Reason: {}", cause);
+            }
+        }
+
+        match self.jobs.values().map(|job| job.next_run).min() {
+            Some(next_run) => next_run.saturating_duration_since(now).min(SCHEDULER_TICK),
+            None => {
+                trace!(
+                    "No services found under {}, throttling..",
+                    self.context.services_dir
+                );
+                SCHEDULER_TICK
+            }
+        }
+    }
+
+
+    /// stops watching definitions, waits for runs in progress to finish
+    /// and handles services according to shutdown policy of context
+    #[instrument(skip(self))]
+    pub fn shutdown(&mut self) {
+        self.watcher = None;
+        let in_progress = || self.jobs.values().any(|job| job.running.load(Ordering::SeqCst));
+        if in_progress() {
+            info!("Waiting for runs in progress to finish..");
+            while in_progress() {
+                sleep(SHUTDOWN_POLL)
+            }
+        }
+        match self.context.on_shutdown {
+            OnShutdown::Leave => info!("Leaving services running."),
+            OnShutdown::Stop => {
+                info!("Stopping services.");
+                for service in self.services().iter().rev() {
+                    match service.stop_service() {
+                        Ok(ok) => info!("{}", ok),
+                        Err(cause) => error!("Failed to stop: {}. Reason: {}", service, cause),
+                    }
+                }
+            }
+        }
+    }
+
+
+    /// stops service with its previous definition and starts it with current one
+    #[instrument]
+    fn restart(context: &Context, previous: &Service, service: &Service) {
+        if context.state.is_paused(&service.name()) {
+            debug!("Monitoring of: {} is paused. Restart skipped.", service);
+            return;
+        }
+        info!("Restarting: {} with changed definition", service);
+        match previous.stop_service() {
+            Ok(ok) => info!("{}", ok),
+            Err(cause) => warn!("Stop failed, starting anyway. Reason: {}", cause),
+        }
+        match service.start_service() {
+            Ok(pid) => {
+                context.state.record_restart(&service.name(), pid);
+                info!("Service restarted: {} with pid: {}", service, pid)
+            }
+            Err(cause) => {
+                error!("Failed to restart service. Reason: {}", cause);
+                context.state.record_failure(&service.name(), &cause);
+            }
+        }
+    }
+}
@@ -3,22 +3,218 @@ use regex::Regex;
 use std::{
     fmt::{self, Display},
     fs::File,
-    io::{prelude::*, Error, ErrorKind},
+    io::{prelude::*, Error},
 };
+use serde::{
+    de::{
+        value::{Error as DeError, MapAccessDeserializer},
+        Error as _, MapAccess, Visitor,
+    },
+    forward_to_deserialize_any, Deserialize, Deserializer,
+};
+use strsim::levenshtein;
 use toml::{de::Error as TomlError, *};
 
 use crate::{
-    common::*,
     init_fields::InitFields,
     mortal::Mortal::{self, *},
+    perun::expectation,
     *,
 };
 
 
+/// amount of definition lines shown around decode error
+static ERROR_SNIPPET_LINES: usize = 2;
+
+
+/// maximum edit distance between unknown key and field name to suggest it
+static SUGGESTION_DISTANCE: usize = 3;
+
+
+/// definition keys which require restart of running service when changed
+pub static RESTART_KEYS: [&str; 3] = ["start", "user", "work_dir"];
+
+
+/// Deserializer capturing list of fields of deserialized struct
+struct FieldsCapture<'a>(&'a mut Option<&'static [&'static str]>);
+
+
+impl<'de> Deserializer<'de> for FieldsCapture<'_> {
+    type Error = DeError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, _visitor: V) -> Result<V::Value, DeError> {
+        Err(DeError::custom("only structs can be captured"))
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        fields: &'static [&'static str],
+        _visitor: V,
+    ) -> Result<V::Value, DeError> {
+        *self.0 = Some(fields);
+        Err(DeError::custom("fields captured"))
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string bytes
+        byte_buf option unit unit_struct newtype_struct seq tuple tuple_struct map
+        enum identifier ignored_any
+    }
+}
+
+
+/// returns names of all fields accepted by deserialized struct
+fn fields_of<'de, T: Deserialize<'de>>() -> &'static [&'static str] {
+    let mut fields = None;
+    let _: Result<T, DeError> = T::deserialize(FieldsCapture(&mut fields));
+    fields.unwrap_or_default()
+}
+
+
+/// returns keys of table missing in fields (prefixed), each with the closest field name
+fn unknown_in(
+    table: &value::Table,
+    fields: &[&str],
+    prefix: &str,
+) -> Vec<(String, Option<String>)> {
+    table
+        .keys()
+        .filter(|key| !fields.contains(&key.as_str()))
+        .map(|key| {
+            let suggestion = fields
+                .iter()
+                .map(|field| (levenshtein(key, field), field))
+                .filter(|(distance, _)| *distance <= SUGGESTION_DISTANCE.min(key.len() / 3))
+                .min_by_key(|(distance, _)| *distance)
+                .map(|(_, field)| field.to_string());
+            (format!("{}{}", prefix, key), suggestion)
+        })
+        .collect()
+}
+
+
+/// URL check with expectations of its response
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct UrlCheck {
+    /// url to request
+    pub url: String,
+
+    /// HTTP method of request. GET by default - POST if body is set
+    pub method: Option<String>,
+
+    /// request headers like: ["Content-Type: application/json"].
+    /// May hold credentials - never serialized
+    #[serde(skip_serializing)]
+    pub headers: Option<Vec<String>>,
+
+    /// request body. May hold credentials - never serialized
+    #[serde(skip_serializing)]
+    pub body: Option<String>,
+
+    /// accepted response status codes. Any below 400 by default
+    pub expect_status: Option<Vec<u32>>,
+
+    /// expected response body contents - regex if wrapped in slashes
+    #[serde(default, deserialize_with = "expected_response")]
+    pub expect_body: Option<String>,
+
+    /// required response headers like: ["X-Version", "Content-Type: application/json"].
+    /// Header value is matched only if given
+    pub expect_headers: Option<Vec<String>>,
+
+    /// maximum response time in ms
+    pub max_latency: Option<u64>,
+}
+
+
+/// URL check definition - plain url or table with url and expectations of its response
+enum UrlDefinition {
+    Url(String),
+    Check(UrlCheck),
+}
+
+
+/// Visitor of URL check definition - keeps errors of table fields (unlike untagged enum)
+struct UrlDefinitionVisitor;
+
+
+impl<'de> Visitor<'de> for UrlDefinitionVisitor {
+    type Value = UrlDefinition;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("url or table with url and expectations of its response")
+    }
+
+    fn visit_str<E: serde::de::Error>(self, url: &str) -> Result<UrlDefinition, E> {
+        Ok(UrlDefinition::Url(url.to_string()))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, map: A) -> Result<UrlDefinition, A::Error> {
+        UrlCheck::deserialize(MapAccessDeserializer::new(map)).map(UrlDefinition::Check)
+    }
+}
+
+
+impl<'de> Deserialize<'de> for UrlDefinition {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<UrlDefinition, D::Error> {
+        deserializer.deserialize_any(UrlDefinitionVisitor)
+    }
+}
+
+
+impl From<UrlDefinition> for UrlCheck {
+    fn from(definition: UrlDefinition) -> UrlCheck {
+        match definition {
+            UrlDefinition::Url(url) => {
+                UrlCheck {
+                    url,
+                    ..UrlCheck::default()
+                }
+            }
+            UrlDefinition::Check(check) => check,
+        }
+    }
+}
+
+
+impl Display for UrlCheck {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.url)
+    }
+}
+
+
+/// deserializes expected response - verifying that it's a valid regex if wrapped in slashes
+fn expected_response<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<String>, D::Error> {
+    let expected: Option<String> = Option::deserialize(deserializer)?;
+    if let Some(expected) = &expected {
+        if let Err(cause) = expectation(expected) {
+            return Err(D::Error::custom(format!(
+                "invalid expected response: {}",
+                cause
+            )));
+        }
+    }
+    Ok(expected)
+}
+
+
+/// deserializes URL checks defined as plain urls or tables
+fn url_checks<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<Vec<UrlCheck>>, D::Error> {
+    let definitions: Option<Vec<UrlDefinition>> = Option::deserialize(deserializer)?;
+    Ok(definitions.map(|definitions| definitions.into_iter().map(UrlCheck::from).collect()))
+}
+
+
 // Service structure is a generic service representation.
 //
 
-#[derive(Deserialize, Debug, Clone, Default)]
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
 pub struct Service {
     /// Service name. Can be overridden. By default it grabs name of ini file.
     name: Option<String>,
@@ -26,6 +222,13 @@ pub struct Service {
     /// default initialization file of service
     ini_file: Option<String>,
 
+    /// dir containing initialization file of service
+    #[serde(skip)]
+    services_dir: Option<String>,
+
+    /// reject unknown keys in service definition. Enabled by default
+    pub strict: Option<bool>,
+
     /// ----------------
     /// General settings
     /// ----------------
@@ -42,18 +245,65 @@ pub struct Service {
     /// CHECKS_URL_TIMEOUT
     pub checks_url_timeout: Option<u64>,
 
+    /// CHECKS_TCP_TIMEOUT
+    pub checks_tcp_timeout: Option<u64>,
+
+    /// CHECKS_RESPONSE_TIMEOUT
+    pub checks_response_timeout: Option<u64>,
+
     /// DEATHWATCHES_INTERVAL
     pub deathwatches_interval: Option<u64>,
 
-    /// Slack webhook url for notification
+    /// Slack webhook url for notification. Secret - never serialized
+    #[serde(skip_serializing)]
     pub slack_webhook_url: Option<String>,
 
     /// slack default channel to send notification, default is set in common
     pub slack_alert_channel: Option<String>,
 
+    /// ALERT_REMINDER_INTERVAL
+    pub alert_reminder_interval: Option<u64>,
+
+    /// names of notifiers (from global notifiers config) to send notifications with.
+    /// Slack settings above are used if unset
+    pub notifiers: Option<Vec<String>>,
+
     /// determines directory to jump - before starting service
     pub work_dir: Option<String>,
 
+    /// file to write service standard output to
+    pub stdout_log: Option<String>,
+
+    /// file to write service standard error to
+    pub stderr_log: Option<String>,
+
+    /// file to write both service outputs to (if stdout_log or stderr_log is unset)
+    pub log_file: Option<String>,
+
+    /// LOG_MAX_SIZE
+    pub log_max_size: Option<u64>,
+
+    /// LOG_ROTATE_COUNT
+    pub log_rotate_count: Option<usize>,
+
+    /// LOG_TAIL_LINES
+    pub log_tail_lines: Option<usize>,
+
+    /// FAILURE_THRESHOLD
+    pub failure_threshold: Option<u64>,
+
+    /// SUCCESS_THRESHOLD
+    pub success_threshold: Option<u64>,
+
+    /// MAX_RESTARTS
+    pub max_restarts: Option<usize>,
+
+    /// RESTARTS_WINDOW
+    pub restarts_window: Option<u64>,
+
+    /// RESTART_BACKOFF_MAX
+    pub restart_backoff_max: Option<u64>,
+
     /// ------------
     /// Veles spawns
     /// ------------
@@ -64,21 +314,89 @@ pub struct Service {
     /// default commands to cleanup service mess (like hanged master process)
     pub cleanup: Option<String>,
 
-    // pub configure: Option<String>,
-    // pub after_start: Option<String>,
-    // pub stop: Option<String>,
-    // pub after_stop: Option<String>,
-    // pub reload: Option<String>,
-    // pub validate: Option<String>,
+    /// service process stays in foreground. Veles keeps it as a managed child,
+    /// writes its pid file and restarts it when it exits.
+    /// Its shell wrapper is replaced with single 'start' command (multi-line is invalid)
+    /// Commands can start it only through running Deities
+    pub foreground: Option<bool>,
+
+    /// commands to validate service configuration. Service won't be started if they fail
+    pub validate: Option<String>,
+
+    /// commands to prepare service environment before each start
+    pub configure: Option<String>,
+
+    /// commands to run right after service was started
+    pub after_start: Option<String>,
+
+    /// commands to stop service (instead of sending signals to its pid)
+    pub stop: Option<String>,
+
+    /// commands to run right after service was stopped
+    pub after_stop: Option<String>,
+
+    /// commands to reload service configuration (on SIGHUP to deities)
+    pub reload: Option<String>,
+
+    /// HOOK_TIMEOUT
+    pub hook_timeout: Option<u64>,
+
+    /// names of services which have to pass their checks before this one is started
+    pub requires: Option<Vec<String>>,
+
+    /// names of services to start before this one - if they're defined
+    pub after: Option<Vec<String>>,
+
     /// ------------
     /// Perun checks
     /// ------------
     /// watch if service domains is a vector of PROTO+FQDN elements like: ["https://my.shiny.domain.com/page2?param=1", "http://some.com"]
-    pub urls: Option<Vec<String>>,
+    /// or tables with url and expectations of its response (see UrlCheck)
+    #[serde(default, deserialize_with = "url_checks")]
+    pub urls: Option<Vec<UrlCheck>>,
+
+    /// TLS_EXPIRY_WARN_DAYS
+    pub tls_expiry_warn_days: Option<u64>,
+
+    /// CA certificates to verify TLS peers of urls with (CA_FILE). System bundle by default
+    pub ca_file: Option<String>,
+
+    /// client certificate (PEM) presented to urls requiring mutual TLS
+    pub client_cert: Option<String>,
+
+    /// private key (PEM) of client certificate
+    pub client_key: Option<String>,
+
+    /// addresses to resolve hosts of urls to, like: ["my.shiny.domain.com:443:127.0.0.1"]
+    pub resolve: Option<Vec<String>>,
+
+    /// skip verification of TLS peers of urls (like self-signed ones). Disabled by default
+    pub insecure: Option<bool>,
 
     // watch service availability through UNIX socket:
     pub unix_socket: Option<String>,
 
+    /// message sent through UNIX socket after connection, like: "PING\r\n".
+    /// May hold credentials - never serialized
+    #[serde(skip_serializing)]
+    pub unix_socket_send: Option<String>,
+
+    /// expected response from UNIX socket - regex if wrapped in slashes, like: "/^\\+PONG/"
+    #[serde(default, deserialize_with = "expected_response")]
+    pub unix_socket_expect: Option<String>,
+
+    /// watch service availability through TCP addresses like: ["127.0.0.1:5432", "[::1]:6379"]
+    pub tcp: Option<Vec<String>>,
+
+    /// message sent to each TCP address after connection. May hold credentials - never
+    /// serialized
+    #[serde(skip_serializing)]
+    pub tcp_send: Option<String>,
+
+    /// expected response from each TCP address - regex if wrapped in slashes
+    #[serde(default, deserialize_with = "expected_response")]
+    pub tcp_expect: Option<String>,
+
     /// minimum disk free space in MiB
     pub disk_minimum_space: Option<i64>,
 
@@ -104,43 +422,220 @@ impl Service {
     }
 
 
+    /// loads service definition from file in services dir of given context
     #[instrument]
-    pub fn from(file_name: String) -> Result<Service, Mortal> {
-        let def_abspath = format!("{}/{}", SERVICES_DIR, file_name);
+    pub fn from(context: &Context, file_name: String) -> Result<Service, Mortal> {
+        let def_abspath = format!("{}/{}", context.services_dir, file_name);
         match Service::load_definition(def_abspath) {
             Ok(service_definition) => {
-                let service_config: Result<Service, TomlError> =
-                    from_str(service_definition.as_ref());
-                match service_config {
-                    Ok(service) => {
-                        Ok(Service {
+                Service::parse(context, file_name, &service_definition)
+            }
+            Err(cause) => {
+                Err(DefinitionLoadFailure {
+                    ini_name: file_name,
+                    cause: Error::other(cause.to_string()),
+                })
+            }
+        }
+    }
+
+
+    /// parses service definition of file in services dir of given context
+    #[instrument(skip(service_definition))]
+    pub fn parse(
+        context: &Context,
+        file_name: String,
+        service_definition: &str,
+    ) -> Result<Service, Mortal> {
+        let service_config: Result<Service, TomlError> = from_str(service_definition);
+        match service_config {
+            Ok(service) => {
+                let unknown = match service.strict {
+                    Some(false) => vec![],
+                    _ => Service::unknown_keys(service_definition),
+                };
+                match unknown.len() {
+                    0 => {
+                        let service = Service {
                             ini_file: Some(file_name),
+                            services_dir: Some(context.services_dir.clone()),
                             ..service
-                        })
+                        };
+                        let start = service.start.clone().unwrap_or_default();
+                        if service.foreground() && start.trim().lines().count() > 1 {
+                            return Err(DefinitionInvalid {
+                                ini_name: service.ini_file(),
+                                key: "start".to_string(),
+                                reason: "foreground service has to start with single command"
+                                    .to_string(),
+                            });
+                        }
+                        Ok(service)
                     }
-                    Err(_) => {
-                        Err(DefinitionDecodeFailure {
+                    _ => {
+                        Err(DefinitionUnknownKeys {
                             ini_name: file_name,
-                            cause: Error::new(
-                                ErrorKind::Other,
-                                "Definition parse error! (detailed parse errors \
-                                               NYD!)"
-                                    .to_string(),
-                            ),
+                            keys: unknown,
                         })
                     }
                 }
             }
             Err(cause) => {
-                Err(DefinitionLoadFailure {
+                let key = Service::error_key(&cause);
+                // NOTE: invalid values of known type are reported at beginning of definition:
+                let location = match (cause.line_col(), &key) {
+                    (Some((0, 0)), Some(key)) => {
+                        Service::key_location(service_definition, key).or(Some((0, 0)))
+                    }
+                    (location, _) => location,
+                };
+                Err(DefinitionDecodeFailure {
                     ini_name: file_name,
-                    cause: Error::new(ErrorKind::Other, cause.to_string()),
+                    line: location.map(|(line, _)| line + 1),
+                    column: location.map(|(_, column)| column + 1),
+                    key,
+                    snippet: location
+                        .map(|(line, column)| {
+                            Service::error_snippet(service_definition, line, column)
+                        })
+                        .unwrap_or_default(),
+                    cause,
                 })
             }
         }
     }
 
 
+    /// returns keys added, removed or changed between two versions of service definition
+    #[instrument(skip(previous, current))]
+    pub fn changed_keys(previous: &str, current: &str) -> Vec<String> {
+        let table = |definition: &str| {
+            match from_str::<Value>(definition) {
+                Ok(Value::Table(table)) => table,
+                _ => value::Table::new(),
+            }
+        };
+        let (previous, current) = (table(previous), table(current));
+        let mut keys: Vec<String> = previous
+            .keys()
+            .chain(current.keys())
+            .filter(|key| previous.get(*key) != current.get(*key))
+            .cloned()
+            .collect();
+        keys.sort();
+        keys.dedup();
+        keys
+    }
+
+
+    /// returns services sorted in start order - each one after its dependencies.
+    /// Services within dependency cycle are appended in their original order
+    #[instrument(skip(services))]
+    pub fn start_order(services: Vec<Service>) -> Vec<Service> {
+        let mut pending = services;
+        let mut ordered: Vec<Service> = vec![];
+        while !pending.is_empty() {
+            let names: Vec<String> = pending.iter().map(|service| service.name()).collect();
+            let (ready, blocked): (Vec<Service>, Vec<Service>) =
+                pending.into_iter().partition(|service| {
+                    service
+                        .requires()
+                        .iter()
+                        .chain(service.after().iter())
+                        .all(|dependency| !names.contains(dependency))
+                });
+            if ready.is_empty() {
+                warn!(
+                    "Dependency cycle between services: {}",
+                    blocked
+                        .iter()
+                        .map(|service| service.name())
+                        .collect::<Vec<String>>()
+                        .join(", ")
+                );
+                ordered.extend(blocked);
+                break;
+            }
+            ordered.extend(ready);
+            pending = blocked;
+        }
+        ordered
+    }
+
+
+    /// returns names of all fields accepted in service definition
+    pub fn fields() -> &'static [&'static str] {
+        lazy_static! {
+            static ref FIELDS: &'static [&'static str] = fields_of::<Service>();
+        }
+        &FIELDS
+    }
+
+
+    /// returns unknown keys of definition (also in its url checks),
+    /// each with the closest known field name
+    #[instrument(skip(definition))]
+    pub fn unknown_keys(definition: &str) -> Vec<(String, Option<String>)> {
+        lazy_static! {
+            static ref URL_FIELDS: &'static [&'static str] = fields_of::<UrlCheck>();
+        }
+        match from_str::<Value>(definition) {
+            Ok(Value::Table(table)) => {
+                let mut unknown = unknown_in(&table, Service::fields(), "");
+                if let Some(Value::Array(urls)) = table.get("urls") {
+                    for (index, url) in urls.iter().enumerate() {
+                        if let Value::Table(check) = url {
+                            let prefix = format!("urls[{}].", index);
+                            unknown.extend(unknown_in(check, &URL_FIELDS, &prefix));
+                        }
+                    }
+                }
+                unknown
+            }
+            _ => vec![],
+        }
+    }
+
+
+    /// returns key name mentioned by TOML decoder error (if any)
+    #[instrument]
+    pub fn error_key(cause: &TomlError) -> Option<String> {
+        lazy_static! {
+            static ref KEY: Regex = Regex::new(r"for key `([^`]*)`").unwrap();
+        }
+        KEY.captures(&cause.to_string())
+            .and_then(|captures| captures.get(1))
+            .map(|key| key.as_str().to_string())
+    }
+
+
+    /// returns (zero based) location of first definition of given key
+    #[instrument(skip(definition))]
+    pub fn key_location(definition: &str, key: &str) -> Option<(usize, usize)> {
+        let assignment = Regex::new(&format!(r"\b{}\s*=", regex::escape(key))).ok()?;
+        definition.lines().enumerate().find_map(|(line, content)| {
+            assignment.find(content).map(|found| (line, found.start()))
+        })
+    }
+
+
+    /// returns definition lines around (zero based) location, with error position marked
+    #[instrument(skip(definition))]
+    pub fn error_snippet(definition: &str, line: usize, column: usize) -> String {
+        let lines: Vec<&str> = definition.lines().collect();
+        let first = line.saturating_sub(ERROR_SNIPPET_LINES);
+        let last = (line + ERROR_SNIPPET_LINES).min(lines.len().saturating_sub(1));
+        let mut snippet = vec![];
+        for (index, content) in lines.iter().enumerate().take(last + 1).skip(first) {
+            snippet.push(format!("{:>4} | {}", index + 1, content));
+            if index == line {
+                snippet.push(format!("{:>4} | {}^", "", " ".repeat(column)));
+            }
+        }
+        snippet.join("\n")
+    }
+
+
     /// loads raw file as String
     #[instrument]
     pub fn load_raw(file_name: String) -> Result<String, Mortal> {
@@ -177,9 +672,16 @@ impl Service {
     }
 
 
+    /// returns dir containing service definition - known if service was loaded by Context
+    #[instrument]
+    pub fn services_dir(&self) -> Option<String> {
+        self.services_dir.clone()
+    }
+
+
     /// returns init file name
     #[instrument]
-    fn ini_file(&self) -> String {
+    pub fn ini_file(&self) -> String {
         match self.ini_file.clone() {
             Some(file_path) => file_path,
             None => "".to_string(),
@@ -206,19 +708,154 @@ impl Display for Service {
             "" => "".to_string(),
             _ => format!(", unix_socket: {}", slf.unix_socket()),
         };
+        let optional_tcp_entries = match slf.tcp().len() {
+            0 => "".to_string(),
+            _ => format!(", tcp: [{}]", slf.tcp().join(", ")),
+        };
         let optional_urls_entries = match slf.urls().len() {
             0 => "".to_string(),
-            _ => format!(", urls: [{}]", slf.urls().join(", ")),
+            _ => {
+                format!(
+                    ", urls: [{}]",
+                    slf.urls()
+                        .iter()
+                        .map(|check| check.to_string())
+                        .collect::<Vec<String>>()
+                        .join(", ")
+                )
+            }
         };
 
         let info_blk = format!(
-            "Service(name: {}, ini: {}{}{}{})",
+            "Service(name: {}, ini: {}{}{}{}{})",
             slf.name(),
             slf.ini_file(),
             optional_pid_entry,
             optional_sock_entry,
+            optional_tcp_entries,
             optional_urls_entries,
         );
         write!(f, "{info_blk}")
     }
 }
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+
+    fn service(name: &str, requires: &[&str], after: &[&str]) -> Service {
+        let names = |names: &[&str]| Some(names.iter().map(|name| name.to_string()).collect());
+        Service {
+            name: Some(name.to_string()),
+            requires: names(requires),
+            after: names(after),
+            ..Service::default()
+        }
+    }
+
+
+    fn names(services: Vec<Service>) -> Vec<String> {
+        services.iter().map(|service| service.name()).collect()
+    }
+
+
+    #[test]
+    fn unknown_keys_are_suggested_closest_fields() {
+        let definition = r#"
+            strat = "true"
+            pid_fil = "/tmp/service.pid"
+            urls = ["http://localhost/", { url = "http://localhost/", expect_stauts = [200] }]
+        "#;
+        assert_eq!(
+            Service::unknown_keys(definition),
+            vec![
+                ("pid_fil".to_string(), Some("pid_file".to_string())),
+                ("strat".to_string(), None),
+                ("urls[1].expect_stauts".to_string(), Some("expect_status".to_string())),
+            ]
+        );
+        assert!(Service::unknown_keys("start = \"true\"").is_empty());
+    }
+
+
+    #[test]
+    fn error_key_is_taken_from_decode_error() {
+        let cause = from_str::<Service>("start = 1").unwrap_err();
+        assert_eq!(Service::error_key(&cause), Some("start".to_string()));
+        let cause = from_str::<Service>("start = ").unwrap_err();
+        assert_eq!(Service::error_key(&cause), None);
+    }
+
+
+    #[test]
+    fn key_location_points_at_assignment() {
+        let definition = "start = \"true\"\n  pid_file= \"/tmp/service.pid\"";
+        assert_eq!(Service::key_location(definition, "pid_file"), Some((1, 2)));
+        assert_eq!(Service::key_location(definition, "file"), None);
+    }
+
+
+    #[test]
+    fn error_snippet_marks_location() {
+        let definition = "a = 1\nb = 2\nc = 3\nd = 4\ne = 5\nf = 6";
+        assert_eq!(
+            Service::error_snippet(definition, 3, 4),
+            [
+                "   2 | b = 2",
+                "   3 | c = 3",
+                "   4 | d = 4",
+                "     |     ^",
+                "   5 | e = 5",
+                "   6 | f = 6",
+            ]
+            .join("\n")
+        );
+        assert_eq!(
+            Service::error_snippet(definition, 0, 0),
+            "   1 | a = 1\n     | ^\n   2 | b = 2\n   3 | c = 3"
+        );
+    }
+
+
+    #[test]
+    fn secrets_are_not_serialized() {
+        let definition = r#"
+            slack_webhook_url = "https://hooks.slack.com/services/secret"
+            tcp = ["127.0.0.1:6379"]
+            tcp_send = "AUTH secret\r\n"
+            unix_socket_send = "AUTH secret\r\n"
+            [[urls]]
+            url = "http://localhost/"
+            headers = ["Authorization: secret"]
+            body = "secret"
+        "#;
+        let service: Service = from_str(definition).unwrap();
+        let serialized = serde_json::to_string(&service).unwrap();
+        assert!(!serialized.contains("secret"), "Leaked: {}", serialized);
+        assert!(serialized.contains("127.0.0.1:6379"));
+    }
+
+
+    #[test]
+    fn start_order_puts_dependencies_first() {
+        let services = vec![
+            service("web", &["db"], &["cache"]),
+            service("cache", &[], &[]),
+            service("db", &[], &["cache"]),
+        ];
+        assert_eq!(names(Service::start_order(services)), vec!["cache", "db", "web"]);
+    }
+
+
+    #[test]
+    fn start_order_appends_dependency_cycles() {
+        let services = vec![
+            service("a", &["b"], &[]),
+            service("b", &["a"], &[]),
+            service("c", &["missing"], &[]),
+        ];
+        assert_eq!(names(Service::start_order(services)), vec!["c", "a", "b"]);
+    }
+}
@@ -0,0 +1,551 @@
+use chrono::Local;
+use std::{
+    collections::{hash_map::RandomState, BTreeMap, HashMap},
+    hash::{BuildHasher, Hasher},
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use crate::{
+    event_log::{self, CHECK, FAILURE, RESTART},
+    init_fields::InitFields,
+    metrics::{self, OUTCOME_TOTAL, RESTARTS_TOTAL, SERVICE_UP},
+    mortal::{
+        Blessing,
+        Mortal::{self, *},
+    },
+    service::Service,
+    *,
+};
+
+
+/// runtime status of supervised service
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct ServiceState {
+    /// service name
+    pub name: String,
+
+    /// result of last checks
+    pub last_check: Option<String>,
+
+    /// true if last checks passed
+    pub last_check_ok: Option<bool>,
+
+    /// service is considered down - it failed the same check failure_threshold times in a
+    /// row, and didn't pass checks success_threshold times in a row since then
+    pub down: bool,
+
+    /// amount of failures in a row - by check type
+    pub consecutive_failures: BTreeMap<String, u64>,
+
+    /// amount of passed checks in a row
+    pub consecutive_successes: u64,
+
+    /// time of last checks
+    pub last_check_at: Option<String>,
+
+    /// time of last (re)start by Deities
+    pub last_restart_at: Option<String>,
+
+    /// amount of (re)starts by Deities
+    pub restarts: u64,
+
+    /// current pid of service
+    pub pid: Option<i32>,
+
+    /// monitoring of service is paused
+    pub paused: bool,
+
+    /// Deities gave up restarting service automatically
+    pub given_up: bool,
+
+    /// times of automatic restart attempts within restarts window
+    #[serde(skip)]
+    pub restart_attempts: Vec<Instant>,
+
+    /// next automatic restart is backed off until this time
+    #[serde(skip)]
+    pub restart_backoff_until: Option<Instant>,
+
+    /// time when service was considered down
+    #[serde(skip)]
+    pub down_since: Option<Instant>,
+
+    /// alerts sent about service since it's down - with time each was last sent
+    #[serde(skip)]
+    pub alerts_sent: HashMap<String, Instant>,
+
+    /// alert keys of warnings reported aside of last checks result
+    #[serde(skip)]
+    pub warnings: Vec<String>,
+}
+
+
+// State holds runtime status of all supervised services.
+// It's shared by all clones of Context.
+//
+
+#[derive(Debug, Clone, Default)]
+pub struct State(Arc<Mutex<HashMap<String, ServiceState>>>);
+
+
+impl State {
+    /// modifies status of service with given name
+    pub fn update<F: FnOnce(&mut ServiceState)>(&self, name: &str, change: F) {
+        match self.0.lock() {
+            Ok(mut states) => {
+                let state = states.entry(name.to_string()).or_insert(ServiceState {
+                    name: name.to_string(),
+                    ..ServiceState::default()
+                });
+                change(state)
+            }
+            Err(cause) => error!("State lock poisoned! Reason: {}", cause),
+        }
+    }
+
+
+    /// returns status of service with given name
+    pub fn get(&self, name: &str) -> ServiceState {
+        match self.0.lock() {
+            Ok(states) => {
+                states.get(name).cloned().unwrap_or(ServiceState {
+                    name: name.to_string(),
+                    ..ServiceState::default()
+                })
+            }
+            Err(_) => {
+                ServiceState {
+                    name: name.to_string(),
+                    ..ServiceState::default()
+                }
+            }
+        }
+    }
+
+
+    /// records result of service checks
+    pub fn record_check(
+        &self,
+        name: &str,
+        result: &Result<Blessing, Mortal>,
+        pid: Option<i32>,
+    ) {
+        let outcome: &dyn Outcome = match result {
+            Ok(ok) => ok,
+            Err(failure) => failure,
+        };
+        metrics::gauge(SERVICE_UP, &[("service", name)], outcome.is_ok() as u8 as f64);
+        metrics::increment(
+            OUTCOME_TOTAL,
+            &[
+                ("service", name),
+                ("kind", outcome.kind().name()),
+                ("variant", outcome.variant()),
+            ],
+        );
+        event_log::record_result(CHECK, name, pid, result);
+        self.update(name, |state| {
+            state.last_check = Some(outcome.to_string());
+            state.last_check_ok = Some(result.is_ok());
+            state.last_check_at = Some(Local::now().to_rfc3339());
+            state.pid = pid;
+        })
+    }
+
+
+    /// counts passed and failed checks of service in a row.
+    /// Returns true if service is considered down
+    pub fn assess(&self, service: &Service, result: &Result<Blessing, Mortal>) -> bool {
+        let mut down = false;
+        self.update(&service.name(), |state| {
+            match result {
+                Ok(_) => {
+                    state.consecutive_failures.clear();
+                    state.consecutive_successes += 1;
+                    if state.consecutive_successes >= service.success_threshold() {
+                        state.down = false;
+                    }
+                }
+                Err(failure) => {
+                    state.consecutive_successes = 0;
+                    // NOTE: failure of another check breaks the streak of the others:
+                    let check = failure.check().unwrap_or("other");
+                    state.consecutive_failures.retain(|name, _| name == check);
+                    let failures = state
+                        .consecutive_failures
+                        .entry(check.to_string())
+                        .or_insert(0);
+                    *failures += 1;
+                    if *failures >= service.failure_threshold() && !state.down {
+                        state.down = true;
+                        state.down_since = Some(Instant::now());
+                    }
+                }
+            }
+            down = state.down;
+        });
+        down
+    }
+
+
+    /// marks service as down right away - without waiting for failure_threshold of checks
+    pub fn mark_down(&self, name: &str) {
+        self.update(name, |state| {
+            state.consecutive_successes = 0;
+            if !state.down {
+                state.down = true;
+                state.down_since = Some(Instant::now());
+            }
+        })
+    }
+
+
+    /// records alert about service. Returns false if the same alert was already sent since
+    /// service is down - unless alert reminder interval passed since it was sent
+    pub fn should_alert(&self, service: &Service, alert: &str) -> bool {
+        let now = Instant::now();
+        let reminder = match service.alert_reminder_interval() {
+            0 => None,
+            interval => Some(Duration::from_millis(interval)),
+        };
+        let mut should = true;
+        self.update(&service.name(), |state| {
+            if let Some(sent_at) = state.alerts_sent.get(alert) {
+                should =
+                    reminder.is_some_and(|reminder| now.duration_since(*sent_at) >= reminder);
+            }
+            if should {
+                state.alerts_sent.insert(alert.to_string(), now);
+            }
+        });
+        should
+    }
+
+
+    /// returns downtime of service which recovered after alert was sent about it
+    /// and clears its alert state
+    pub fn take_recovery(&self, name: &str) -> Option<Duration> {
+        let mut downtime = None;
+        self.update(name, |state| {
+            // NOTE: alerts sent while service is up are just warnings - no recovery:
+            if let (false, Some(since)) = (state.down, state.down_since) {
+                let warnings = &state.warnings;
+                if state.alerts_sent.keys().any(|alert| !warnings.contains(alert)) {
+                    downtime = Some(since.elapsed());
+                }
+                state.alerts_sent.retain(|alert, _| warnings.contains(alert));
+                state.down_since = None;
+            }
+        });
+        downtime
+    }
+
+
+    /// records warnings of last checks. Alerts about warnings which stopped appearing
+    /// (like expiring certificate after renewal) are forgotten, to be sent when they return
+    pub fn settle_warnings(&self, name: &str, warnings: &[Mortal]) {
+        let current: Vec<String> = warnings.iter().map(Mortal::alert_key).collect();
+        self.update(name, |state| {
+            for gone in state.warnings.iter().filter(|alert| !current.contains(alert)) {
+                state.alerts_sent.remove(gone);
+            }
+            state.warnings = current;
+        })
+    }
+
+
+    /// records failure of action on service
+    pub fn record_failure(&self, name: &str, failure: &Mortal) {
+        metrics::increment(
+            OUTCOME_TOTAL,
+            &[
+                ("service", name),
+                ("kind", failure.kind().name()),
+                ("variant", failure.variant()),
+            ],
+        );
+        event_log::record(FAILURE, name, None, false, Some(failure), &failure.to_string());
+    }
+
+
+    /// records (re)start of service
+    pub fn record_restart(&self, name: &str, pid: u32) {
+        metrics::increment(RESTARTS_TOTAL, &[("service", name)]);
+        event_log::record(
+            RESTART,
+            name,
+            Some(pid as i32),
+            true,
+            None,
+            "Service (re)started by Deities",
+        );
+        self.update(name, |state| {
+            state.restarts += 1;
+            state.last_restart_at = Some(Local::now().to_rfc3339());
+            state.pid = Some(pid as i32);
+        })
+    }
+
+
+    /// pauses or unpauses monitoring of service
+    pub fn set_paused(&self, name: &str, paused: bool) {
+        self.update(name, |state| state.paused = paused)
+    }
+
+
+    /// returns true if monitoring of service is paused
+    pub fn is_paused(&self, name: &str) -> bool {
+        self.get(name).paused
+    }
+
+
+    /// returns true if Deities gave up restarting service automatically
+    pub fn is_given_up(&self, name: &str) -> bool {
+        self.get(name).given_up
+    }
+
+
+    /// checks if service can be restarted automatically now. Fails if its restart is
+    /// backed off, or if it was restarted too often within restarts window - then Deities
+    /// gives up restarting it
+    pub fn restart_permitted(&self, service: &Service) -> Result<(), Mortal> {
+        let now = Instant::now();
+        let window = Duration::from_millis(service.restarts_window());
+        let mut permitted = Ok(());
+        self.update(&service.name(), |state| {
+            state
+                .restart_attempts
+                .retain(|attempt| now.duration_since(*attempt) < window);
+            let restarts = state.restart_attempts.len();
+            if state.given_up || restarts >= service.max_restarts() {
+                state.given_up = true;
+                permitted = Err(ServiceGivenUp {
+                    service: Box::new(service.clone()),
+                    restarts,
+                });
+            } else if let Some(until) =
+                state.restart_backoff_until.filter(|until| *until > now)
+            {
+                permitted = Err(ServiceRestartBackedOff {
+                    service: Box::new(service.clone()),
+                    remaining: until.duration_since(now).as_millis() as u64,
+                });
+            }
+        });
+        permitted
+    }
+
+
+    /// records automatic restart attempt of service and backs off the next one
+    pub fn record_restart_attempt(&self, service: &Service) {
+        let now = Instant::now();
+        self.update(&service.name(), |state| {
+            state.restart_attempts.push(now);
+            // NOTE: exponential backoff, with jitter - so services don't restart in sync:
+            let exponent = state.restart_attempts.len().saturating_sub(1).min(32) as u32;
+            let backoff = service
+                .checks_interval()
+                .saturating_mul(2u64.saturating_pow(exponent))
+                .min(service.restart_backoff_max());
+            let jitter = RandomState::new().build_hasher().finish() as f64 / u64::MAX as f64;
+            let delay = (backoff as f64 * (0.5 + jitter / 2.0)) as u64;
+            state.restart_backoff_until = Some(now + Duration::from_millis(delay));
+        })
+    }
+
+
+    /// clears restart attempts, backoff and given up state of service
+    pub fn reset_restarts(&self, name: &str) {
+        self.update(name, |state| {
+            state.given_up = false;
+            state.restart_attempts.clear();
+            state.restart_backoff_until = None;
+        })
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mortal::Blessing::OkAllChecks;
+    use std::slice;
+
+
+    fn service() -> Service {
+        let mut service = Service::default();
+        service.failure_threshold = Some(2);
+        service.success_threshold = Some(2);
+        service
+    }
+
+
+    fn pid_failure(service: &Service) -> Result<Blessing, Mortal> {
+        Err(CheckPidfileMalformed {
+            service: Box::new(service.clone()),
+        })
+    }
+
+
+    fn url_failure(service: &Service) -> Result<Blessing, Mortal> {
+        Err(CheckURLAssertion {
+            service: Box::new(service.clone()),
+            url: "http://localhost/".to_string(),
+            status: 500,
+            latency: 1,
+            reason: "Unexpected status: 500".to_string(),
+        })
+    }
+
+
+    fn passed(service: &Service) -> Result<Blessing, Mortal> {
+        Ok(OkAllChecks {
+            service: Box::new(service.clone()),
+            amount: 1,
+        })
+    }
+
+
+    #[test]
+    fn assess_counts_failures_of_the_same_check_in_a_row() {
+        let (state, service) = (State::default(), service());
+        assert!(!state.assess(&service, &pid_failure(&service)));
+        assert!(state.assess(&service, &pid_failure(&service)));
+        assert!(state.get(&service.name()).down_since.is_some());
+    }
+
+
+    #[test]
+    fn assess_resets_failures_of_other_checks() {
+        let (state, service) = (State::default(), service());
+        assert!(!state.assess(&service, &pid_failure(&service)));
+        assert!(!state.assess(&service, &url_failure(&service)));
+        assert!(!state.assess(&service, &pid_failure(&service)));
+        let failures = state.get(&service.name()).consecutive_failures;
+        assert_eq!(failures.get("pid"), Some(&1));
+        assert_eq!(failures.get("urls"), None);
+    }
+
+
+    #[test]
+    fn marked_down_service_fails_first_check() {
+        let (state, service) = (State::default(), service());
+        state.mark_down(&service.name());
+        assert!(state.assess(&service, &pid_failure(&service)));
+        assert!(state.get(&service.name()).down_since.is_some());
+    }
+
+
+    #[test]
+    fn assess_recovers_after_success_threshold() {
+        let (state, service) = (State::default(), service());
+        state.assess(&service, &pid_failure(&service));
+        state.assess(&service, &pid_failure(&service));
+        assert!(state.assess(&service, &passed(&service)));
+        assert!(!state.assess(&service, &passed(&service)));
+        assert!(state.get(&service.name()).consecutive_failures.is_empty());
+    }
+
+
+    fn backoff(state: &State, service: &Service) -> u64 {
+        match state.restart_permitted(service) {
+            Err(ServiceRestartBackedOff {
+                remaining,
+                ..
+            }) => remaining,
+            other => panic!("Restart wasn't backed off: {:?}", other),
+        }
+    }
+
+
+    fn tls_warning(service: &Service, url: &str) -> Mortal {
+        CheckTlsExpiry {
+            service: Box::new(service.clone()),
+            url: url.to_string(),
+            days: 5,
+            expires: "Jan  1 00:00:00 2030 GMT".to_string(),
+        }
+    }
+
+
+    #[test]
+    fn warnings_are_alerted_per_url() {
+        let (state, service) = (State::default(), service());
+        let warnings = [
+            tls_warning(&service, "https://localhost/"),
+            tls_warning(&service, "https://localhost:8443/"),
+        ];
+        state.settle_warnings(&service.name(), &warnings);
+        assert!(state.should_alert(&service, &warnings[0].alert_key()));
+        assert!(state.should_alert(&service, &warnings[1].alert_key()));
+        assert!(!state.should_alert(&service, &warnings[0].alert_key()));
+    }
+
+
+    #[test]
+    fn warnings_are_alerted_again_after_they_stop() {
+        let (state, service) = (State::default(), service());
+        let warning = tls_warning(&service, "https://localhost/");
+        state.settle_warnings(&service.name(), slice::from_ref(&warning));
+        assert!(state.should_alert(&service, &warning.alert_key()));
+        state.settle_warnings(&service.name(), slice::from_ref(&warning));
+        assert!(!state.should_alert(&service, &warning.alert_key()));
+
+        // NOTE: certificate renewed:
+        state.settle_warnings(&service.name(), &[]);
+        state.settle_warnings(&service.name(), slice::from_ref(&warning));
+        assert!(state.should_alert(&service, &warning.alert_key()));
+    }
+
+
+    #[test]
+    fn recovery_keeps_alerts_of_warnings() {
+        let (state, service) = (State::default(), service());
+        let warning = tls_warning(&service, "https://localhost/");
+        state.assess(&service, &pid_failure(&service));
+        state.assess(&service, &pid_failure(&service));
+        assert!(state.should_alert(&service, "CheckPidfileMalformed"));
+        state.settle_warnings(&service.name(), slice::from_ref(&warning));
+        assert!(state.should_alert(&service, &warning.alert_key()));
+        state.assess(&service, &passed(&service));
+        state.assess(&service, &passed(&service));
+        assert!(state.take_recovery(&service.name()).is_some());
+        assert!(!state.should_alert(&service, &warning.alert_key()));
+    }
+
+
+    #[test]
+    fn restart_backoff_grows_up_to_maximum() {
+        let (state, mut service) = (State::default(), service());
+        service.checks_interval = Some(1000);
+        service.restart_backoff_max = Some(3000);
+        service.max_restarts = Some(10);
+        assert!(state.restart_permitted(&service).is_ok());
+        state.record_restart_attempt(&service);
+        assert!((400..=1000).contains(&backoff(&state, &service)));
+        state.record_restart_attempt(&service);
+        assert!((900..=2000).contains(&backoff(&state, &service)));
+        state.record_restart_attempt(&service);
+        state.record_restart_attempt(&service);
+        assert!((1400..=3000).contains(&backoff(&state, &service)));
+    }
+
+
+    #[test]
+    fn restarts_are_given_up_after_maximum_within_window() {
+        let (state, mut service) = (State::default(), service());
+        service.max_restarts = Some(2);
+        service.restarts_window = Some(60000);
+        state.record_restart_attempt(&service);
+        state.record_restart_attempt(&service);
+        match state.restart_permitted(&service) {
+            Err(ServiceGivenUp {
+                restarts,
+                ..
+            }) => assert_eq!(restarts, 2),
+            other => panic!("Restarts weren't given up: {:?}", other),
+        }
+        assert!(state.get(&service.name()).given_up);
+    }
+}
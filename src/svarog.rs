@@ -1,10 +1,6 @@
 use chrono::{DateTime, Local};
 use libc::{self, kill};
 
-// Link
-use slack_hook::{
-    AttachmentBuilder, Field, Parse, PayloadBuilder, Slack, SlackTextContent::Text,
-}; // SlackLink,
 use std::{
     io::{Error, ErrorKind},
     thread::sleep,
@@ -14,13 +10,74 @@ use uname::{uname, Info};
 
 use crate::{
     common::*,
+    event_log::{self, DEATH_WATCH, NOTIFICATION},
     init_fields::InitFields,
-    mortal::Mortal::{self, *},
+    mortal::{
+        Blessing::{self, *},
+        Mortal::{self, *},
+    },
+    notifier::Event,
+    rotating_file::tail_lines,
     service::Service,
     *,
 };
 
 
+/// kind of notification
+#[derive(Debug)]
+pub struct Notice {
+    pub kind: &'static str,
+    pub title: &'static str,
+    pub color: &'static str,
+    pub emoji: &'static str,
+    pub summary: &'static str,
+}
+
+
+/// notification about service failure
+pub static ALERT_NOTICE: Notice = Notice {
+    kind: "alert",
+    title: "ALERT NOTIFICATION",
+    color: "#FF3d41",
+    emoji: ":rotating_light:",
+    summary: "Unstable service detected. Deities will attempt to solve this problem \
+              automatically.",
+};
+
+
+/// notification about service which passes its checks again
+pub static RECOVERY_NOTICE: Notice = Notice {
+    kind: "recovery",
+    title: "RECOVERY NOTIFICATION",
+    color: "#36A64F",
+    emoji: ":white_check_mark:",
+    summary: "Service recovered. It passes its checks again.",
+};
+
+
+/// returns name of signal sent by death watch
+fn signal_name(signal: libc::c_int) -> String {
+    match signal {
+        libc::SIGCONT => "SIGCONT".to_string(),
+        libc::SIGINT => "SIGINT".to_string(),
+        libc::SIGTERM => "SIGTERM".to_string(),
+        libc::SIGKILL => "SIGKILL".to_string(),
+        any => format!("signal {}", any),
+    }
+}
+
+
+/// formats duration like: 1h 2m 3s
+pub fn human_duration(duration: Duration) -> String {
+    let seconds = duration.as_secs();
+    match (seconds / 3600, seconds % 3600 / 60, seconds % 60) {
+        (0, 0, seconds) => format!("{}s", seconds),
+        (0, minutes, seconds) => format!("{}m {}s", minutes, seconds),
+        (hours, minutes, seconds) => format!("{}h {}m {}s", hours, minutes, seconds),
+    }
+}
+
+
 // Svarog is mr Smith - that can do variety of stuff
 //
 pub trait Svarog {
@@ -32,105 +89,148 @@ pub trait Svarog {
     fn sys_info(&self) -> Info;
 
 
-    /// sends Slack alert notifications
-    fn notification(&self, message: String, error: String) -> Result<String, Mortal>;
+    /// sends alert notifications about given failure
+    fn notification(
+        &self,
+        context: &Context,
+        failure: &Mortal,
+        message: String,
+        error: String,
+    ) -> Result<String, Mortal>;
+
+
+    /// sends notification about recovery of service, with its downtime
+    fn recovery_notification(
+        &self,
+        context: &Context,
+        message: String,
+        downtime: Duration,
+    ) -> Result<String, Mortal>;
+
+
+    /// sends notification of given kind with notifiers of service
+    fn send_notification(
+        &self,
+        context: &Context,
+        notice: &Notice,
+        mortal: Option<&Mortal>,
+        message: String,
+        details_name: &str,
+        details: String,
+    ) -> Result<String, Mortal>;
 
 
     /// death_watch will kill service gracefully in case of failure
     /// instead of killing forcefully (kill -9)
-    fn death_watch(&self, signal: libc::c_int) -> Result<Mortal, Mortal>;
+    fn death_watch(&self, signal: libc::c_int) -> Result<Blessing, Mortal>;
 
     /// read pid from service pid file
     fn read_pid(&self) -> Result<i32, Mortal>;
 
     /// returns raw value of pid of service process
     fn pid(&self) -> i32;
+
+    /// returns last lines of service output (stderr log preferred)
+    fn output_tail(&self) -> Vec<String>;
 }
 
 impl Svarog for Service {
-    #[instrument]
-    fn notification(&self, message: String, error: String) -> Result<String, Mortal> {
+    #[instrument(skip(context))]
+    fn notification(
+        &self,
+        context: &Context,
+        failure: &Mortal,
+        message: String,
+        error: String,
+    ) -> Result<String, Mortal> {
+        self.send_notification(
+            context,
+            &ALERT_NOTICE,
+            Some(failure),
+            message,
+            "Error details:",
+            error,
+        )
+    }
+
+
+    #[instrument(skip(context))]
+    fn recovery_notification(
+        &self,
+        context: &Context,
+        message: String,
+        downtime: Duration,
+    ) -> Result<String, Mortal> {
+        self.send_notification(
+            context,
+            &RECOVERY_NOTICE,
+            None,
+            message,
+            "Downtime:",
+            human_duration(downtime),
+        )
+    }
+
+
+    #[instrument(skip(context, notice))]
+    fn send_notification(
+        &self,
+        context: &Context,
+        notice: &Notice,
+        mortal: Option<&Mortal>,
+        message: String,
+        details_name: &str,
+        details: String,
+    ) -> Result<String, Mortal> {
         let local: DateTime<Local> = Local::now();
-        let webhookurl = self.slack_webhook_url();
-        let alertchannel = self.slack_alert_channel();
+        let sys_info = self.sys_info();
+        let event = Event {
+            kind: notice.kind.to_string(),
+            title: notice.title.to_string(),
+            summary: notice.summary.to_string(),
+            color: notice.color.to_string(),
+            emoji: notice.emoji.to_string(),
+            message,
+            service: self.name(),
+            service_details: self.to_string(),
+            details_name: details_name.to_string(),
+            details,
+            hostname: sys_info.nodename.clone(),
+            system: format!(
+                "{} / {} / {} / {}",
+                sys_info.sysname, sys_info.release, sys_info.machine, VERSION
+            ),
+            timestamp: local.naive_local().to_string(),
+            mortal: mortal.and_then(|mortal| serde_json::to_value(mortal).ok()),
+        };
 
-        match &webhookurl[..] {
-            "" => {
-                info!("SLACK_WEBHOOKURL is unset. Slack notifications will NOT be sent!");
-                Ok("Notifiication skipped".to_string())
-            }
-            _ => {
-                match &alertchannel[..] {
-                    "" => {
-                        info!(
-                            "SLACK_ALERTCHANNEL is empty. Slack notigications will NOTE be \
-                               sent!"
-                        );
-                        Ok("Notifiication skipped".to_string())
-                    }
-                    _channel => {
-                        let slack = Slack::new(webhookurl.as_ref()).unwrap();
-                        let p = PayloadBuilder::new()
-                            .attachments(
-                                vec![
-                                    AttachmentBuilder::new(DEFAULT_NOTIFICATION_NAME)
-                                        .title("ALERT NOTIFICATION")
-                                        .author_name(DEFAULT_NOTIFICATION_NAME)
-                                        .author_icon(DEFAULT_VKS_LOGO)
-                                        .color("#FF3d41")
-                                        .text(
-                                            vec![
-                                                Text("Unstable service detected. Deities will attempt to solve this problem automatically.".into()),
-                                                // Link(SlackLink::new("https://google.com", "Google")),
-                                                Text("".into()),
-                                            ].as_slice())
-                                        .fields(
-                                            vec![
-                                                Field::new("", "", Some(false)),
-                                                Field::new("", "", Some(false)),
-                                                Field::new("Message:", message, Some(true)),
-                                                Field::new("Service details:", self.to_string(), Some(true)),
-                                                Field::new("", "", Some(false)),
-                                                Field::new("Host name:", self.sys_info().nodename, Some(true)),
-                                                Field::new(
-                                                    format!("System / Release / Machine / {}", NAME),
-                                                    format!("{} / {} / {} / {}", self.sys_info().sysname, self.sys_info().release, self.sys_info().machine, VERSION),
-                                                    Some(true)),
-                                                Field::new("", "", Some(true)),
-                                                Field::new("Error details:", error, Some(false)),
-                                            ])
-                                        .footer_icon(DEFAULT_VKS_LOGO)
-                                        .footer(vec![
-                                            Text(format!("{}   © 2o16-2o17   |", local.naive_local()).into()),
-                                        ].as_slice())
-                                        .build()
-                                        .unwrap()
-                                ])
-                            .link_names(true)
-                            .unfurl_links(true)
-                            .unfurl_media(true)
-                            .username(DEFAULT_NOTIFICATION_NAME)
-                            .icon_url(DEFAULT_VKS_LOGO)
-                            .icon_emoji(":rotating_light:")
-                            .text("")
-                            .channel(alertchannel.clone())
-                            .parse(Parse::Full)
-                            .build()
-                            .unwrap();
-
-                        let res = slack.send(&p);
-                        match res {
-                            Ok(()) => Ok("Notifiication sent".to_string()),
-                            Err(cause) => {
-                                Err(NotificationFailure {
-                                    cause,
-                                })
-                            }
-                        }
-                    }
+        let mut results = vec![];
+        let mut failure = None;
+        for (name, config) in context.notifiers_for(self)? {
+            match config.notifier().notify(&event) {
+                Ok(result) => {
+                    let result = format!("{}: {}", name, result);
+                    event_log::record(NOTIFICATION, &event.service, None, true, None, &result);
+                    results.push(result);
+                }
+                Err(cause) => {
+                    error!("Notifier: {} failed. Reason: {}", name, cause);
+                    event_log::record(
+                        NOTIFICATION,
+                        &event.service,
+                        None,
+                        false,
+                        Some(&cause),
+                        &format!("{}: {}", name, cause),
+                    );
+                    failure = Some(cause);
                 }
             }
         }
+        match failure {
+            Some(cause) => Err(cause),
+            None => Ok(results.join(", ")),
+        }
     }
 
 
@@ -155,7 +255,7 @@ impl Svarog for Service {
 
 
     #[instrument]
-    fn death_watch(&self, signal: libc::c_int) -> Result<Mortal, Mortal> {
+    fn death_watch(&self, signal: libc::c_int) -> Result<Blessing, Mortal> {
         let pid = match self.pid() {
             -1 => {
                 return Err(SanityCheckFailure {
@@ -190,10 +290,27 @@ impl Svarog for Service {
                     debug!("Deathwatch interval: {} ms", deathwatch_ival);
                     sleep(Duration::from_millis(deathwatch_ival))
                 }
-                if kill(pid, signal) == 0 && kill(pid, 0) != 0 {
+                let interrupted = kill(pid, signal) == 0 && kill(pid, 0) != 0;
+                event_log::record(
+                    DEATH_WATCH,
+                    &self.name(),
+                    Some(pid),
+                    interrupted,
+                    None,
+                    &format!(
+                        "Sent: {} to pid: {}. Process {}",
+                        signal_name(signal),
+                        pid,
+                        match interrupted {
+                            true => "was interrupted",
+                            false => "is still alive",
+                        }
+                    ),
+                );
+                if interrupted {
                     debug!("Process with pid: {}, was interrupted!", pid);
                     return Ok(OkPidInterrupted {
-                        service: self.clone(),
+                        service: Box::new(self.clone()),
                         pid,
                     });
                 }
@@ -209,8 +326,8 @@ impl Svarog for Service {
                     }
                 }
             } else {
-                Err(OkPidAlreadyInterrupted {
-                    service: self.clone(),
+                Ok(OkPidAlreadyInterrupted {
+                    service: Box::new(self.clone()),
                     pid,
                 })
             }
@@ -224,6 +341,19 @@ impl Svarog for Service {
     }
 
 
+    #[instrument]
+    fn output_tail(&self) -> Vec<String> {
+        let log = match self.stderr_log().as_ref() {
+            "" => self.stdout_log(),
+            stderr_log => stderr_log.to_string(),
+        };
+        match log.as_ref() {
+            "" => vec![],
+            path => tail_lines(path, self.log_tail_lines()),
+        }
+    }
+
+
     #[instrument]
     fn read_pid(&self) -> Result<i32, Mortal> {
         match Service::load_raw(self.clone().pid_file()) {
@@ -233,14 +363,14 @@ impl Svarog for Service {
                     Ok(pid) => Ok(pid),
                     Err(_) => {
                         Err(CheckPidfileMalformed {
-                            service: self.clone(),
+                            service: Box::new(self.clone()),
                         })
                     }
                 }
             }
             Err(cause) => {
                 Err(CheckPidfileUnaccessible {
-                    service: self.clone(),
+                    service: Box::new(self.clone()),
                     cause: Error::new(ErrorKind::PermissionDenied, cause.to_string()),
                 })
             }
@@ -275,3 +405,19 @@ impl Svarog for Service {
     //     }
     // }
 }
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+
+    #[test]
+    fn human_duration_skips_empty_units() {
+        assert_eq!(human_duration(Duration::from_millis(999)), "0s");
+        assert_eq!(human_duration(Duration::from_secs(59)), "59s");
+        assert_eq!(human_duration(Duration::from_secs(60)), "1m 0s");
+        assert_eq!(human_duration(Duration::from_secs(3599)), "59m 59s");
+        assert_eq!(human_duration(Duration::from_secs(90061)), "25h 1m 1s");
+    }
+}
@@ -1,37 +1,136 @@
 use std::{
-    fs::{set_permissions, File},
-    io::prelude::*,
+    collections::HashMap,
+    fs::{remove_file, set_permissions, File, OpenOptions},
+    io::{prelude::*, Error, ErrorKind},
     os::unix::{fs::PermissionsExt, process::CommandExt},
-    process::{Command, Stdio},
+    process::{Child, Command, Stdio},
+    sync::Mutex,
+    thread::{sleep, Builder, JoinHandle},
+    time::{Duration, Instant},
 };
 use users::{get_group_by_name, get_user_by_name};
 
 use crate::{
     common::*,
+    event_log::{self, EXIT, RELOAD, START, STOP},
     init_fields::InitFields,
-    mortal::Mortal::{self, *},
+    mortal::{
+        Blessing::{self, *},
+        Mortal::{self, *},
+    },
+    rotating_file::{copy_lines, RotatingFile},
     service::Service,
+    svarog::Svarog,
     *,
 };
 
 
+/// process spawned and supervised in foreground by Veles
+#[derive(Debug, Clone)]
+pub struct Foreground {
+    /// pid of service process
+    pub pid: u32,
+
+    /// false once process has exited and was reaped by its watcher
+    pub running: bool,
+}
+
+
+/// pause between checks if lifecycle hook commands exited
+static HOOK_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+
+lazy_static! {
+    /// foreground processes managed by Veles - by service name
+    pub static ref FOREGROUND: Mutex<HashMap<String, Foreground>> = Mutex::new(HashMap::new());
+}
+
+
+/// true if foreground process of service exited since it was started by Veles
+pub fn foreground_exited(name: &str) -> bool {
+    match FOREGROUND.lock() {
+        Ok(managed) => managed.get(name).is_some_and(|process| !process.running),
+        Err(_) => false,
+    }
+}
+
+
 // Veles is a service spawner deity
 //
 pub trait Veles {
-    fn create_shell_wrapper(&self, commands: String) -> String;
+    fn create_shell_wrapper(&self, services_dir: &str, commands: String) -> String;
 
+    /// starts service and records result of start. Returns pid of started process
     fn start_service(&self) -> Result<u32, Mortal>;
+
+    /// starts service with its 'validate', 'configure' and 'after_start' hooks
+    fn perform_start(&self) -> Result<u32, Mortal>;
+
+    /// stops service and records result of stop
+    fn stop_service(&self) -> Result<Blessing, Mortal>;
+
+    /// stops service with 'stop' commands (or death watch if undefined)
+    fn perform_stop(&self) -> Result<Blessing, Mortal>;
+
+    /// reloads service with 'reload' commands
+    fn reload_service(&self) -> Result<Blessing, Mortal>;
+
+    /// runs lifecycle hook commands and waits for their exit status.
+    /// Commands running longer than hook_timeout are killed
+    fn run_hook(&self, hook: &str, commands: &Option<String>) -> Result<(), Error>;
+
+    /// spawns thread reading whole output stream of hook commands
+    fn spawn_reader<R: Read + Send + 'static>(
+        &self,
+        reader: Option<R>,
+    ) -> Result<JoinHandle<Vec<u8>>, Error>;
+
+    /// sends service output to its log files (or /dev/null if unset)
+    fn redirect_output(&self, cmd: &mut Command);
+
+    /// returns destination of service output written to given log file
+    fn output_sink(&self, path: &str) -> Stdio;
+
+    /// copies piped output of foreground process to its rotated log files
+    fn capture_output(&self, child: &mut Child) -> Vec<JoinHandle<()>>;
+
+    /// spawns thread copying single output stream to log file
+    fn spawn_copier<R: Read + Send + 'static>(
+        &self,
+        stream: &str,
+        reader: R,
+        path: String,
+    ) -> Option<JoinHandle<()>>;
+
+    /// writes collected output to log file
+    fn write_output(&self, path: &str, output: &[u8]);
+
+    /// rotates log files written directly by daemon, once they grow over log_max_size
+    fn rotate_logs(&self);
+
+    /// sets service user and group of spawned command
+    fn set_credentials(&self, cmd: &mut Command);
+
+    /// spawns service process as managed child and starts its watcher thread.
+    /// Returns pid of process and false if it was already started meanwhile
+    fn spawn_foreground(&self, cmd: Command) -> Result<(u32, bool), Mortal>;
+
+    /// waits for foreground process to exit and reaps it
+    fn watch_foreground(&self, child: Child);
+
+    /// writes pid of foreground process to service pid file
+    fn write_pid_file(&self, pid: u32);
 }
 
 
 impl Veles for Service {
     #[instrument]
-    fn create_shell_wrapper(&self, commands: String) -> String {
-        let wrapper = format!("{}/.{}.sh", SERVICES_DIR, self.name());
+    fn create_shell_wrapper(&self, services_dir: &str, commands: String) -> String {
+        let wrapper = format!("{}/.{}.sh", services_dir, self.name());
         match File::create(wrapper.clone()) {
             Ok(mut file) => {
                 match file
-                    .write(format!("#!/bin/sh\nexport PATH={}\n", DEFAULT_PATH).as_bytes())
+                    .write_all(format!("#!/bin/sh\nexport PATH={}\n", DEFAULT_PATH).as_bytes())
                 {
                     Ok(_) => {
                         // If cleanup routines defined, inject it before spawn:
@@ -39,7 +138,7 @@ impl Veles for Service {
                             Some(cleanup) => {
                                 let cl =
                                     format!("\n#Pre-start cleanup routine:\n{}\n\n", cleanup);
-                                match file.write(cl.as_bytes()) {
+                                match file.write_all(cl.as_bytes()) {
                                     Ok(_) => trace!("Cleanup routine written successfully"),
                                     Err(we) => error!("Cleanup write error!. Reason: {}", we),
                                 }
@@ -47,7 +146,7 @@ impl Veles for Service {
                             None => trace!("No cleanup routine to inject. Skipped."),
                         }
 
-                        match file.write(commands.as_bytes()) {
+                        match file.write_all(commands.as_bytes()) {
                             Ok(_) => {
                                 match file.flush() {
                                     Ok(_) => trace!("Flushed successfully"),
@@ -79,12 +178,72 @@ impl Veles for Service {
 
     #[instrument]
     fn start_service(&self) -> Result<u32, Mortal> {
+        let started = self.perform_start();
+        match started {
+            Ok(pid) => {
+                event_log::record(
+                    START,
+                    &self.name(),
+                    Some(pid as i32),
+                    true,
+                    None,
+                    "Service started",
+                )
+            }
+            Err(ref failure) => {
+                event_log::record(
+                    START,
+                    &self.name(),
+                    None,
+                    false,
+                    Some(failure),
+                    &failure.to_string(),
+                )
+            }
+        }
+        started
+    }
+
+
+    #[instrument]
+    fn perform_start(&self) -> Result<u32, Mortal> {
         let mut cmd = Command::new(DEFAULT_SHELL);
-        cmd.arg("-c");
         match self.start {
             Some(ref commands) => {
-                // NOTE: single command - a wrapper:
-                cmd.arg(self.create_shell_wrapper(commands.to_string()));
+                // NOTE: shell wrapper is written to services dir of definition:
+                let services_dir = match self.services_dir() {
+                    Some(services_dir) => services_dir,
+                    None => {
+                        return Err(ServiceStartFailure {
+                            service: Box::new(self.clone()),
+                            cause: Error::other("Service wasn't loaded from services dir"),
+                            output: vec![],
+                        });
+                    }
+                };
+                // NOTE: configuration is validated once it's written by 'configure' commands:
+                if let Err(cause) = self.run_hook("configure", &self.configure) {
+                    return Err(ServiceConfigureFailure {
+                        service: Box::new(self.clone()),
+                        cause,
+                    });
+                }
+                if let Err(cause) = self.run_hook("validate", &self.validate) {
+                    return Err(ServiceValidateFailure {
+                        service: Box::new(self.clone()),
+                        cause,
+                    });
+                }
+
+                if self.foreground() {
+                    // NOTE: wrapper replaces itself with service process (we get service pid):
+                    let command = format!("exec {}", commands.trim());
+                    cmd.arg(self.create_shell_wrapper(&services_dir, command));
+                } else {
+                    // NOTE: single command - a wrapper:
+                    cmd.arg("-c");
+                    cmd.arg(self.create_shell_wrapper(&services_dir, commands.to_string()));
+                }
 
                 cmd.current_dir(self.work_dir());
                 trace!(
@@ -95,46 +254,547 @@ impl Veles for Service {
 
                 // NOTE: always set stdin to null:
                 cmd.stdin(Stdio::null());
-                cmd.stdout(Stdio::null());
-                cmd.stderr(Stdio::null());
+                self.redirect_output(&mut cmd);
+                self.set_credentials(&mut cmd);
 
-                match get_user_by_name(&self.user()) {
-                    Some(uid) => {
-                        trace!("Setting service UID of valid user: {:?}", uid.name());
-                        cmd.uid(uid.uid());
+                let pid = match self.foreground() {
+                    true => {
+                        match self.spawn_foreground(cmd)? {
+                            (pid, true) => pid,
+                            // NOTE: hooks were run by start which spawned the process:
+                            (pid, false) => return Ok(pid),
+                        }
                     }
-                    None => warn!("Username {} not found in system!", self.user()),
-                }
+                    false => {
+                        match cmd.spawn() {
+                            Ok(mut child) => {
+                                let pid = child.id();
+                                warn!(
+                                    "NOTE: Service is supposed to go in background (daemonize)!"
+                                );
+                                let status =
+                                    child.wait().expect("Service should spawn in background!");
+                                if !status.success() {
+                                    return Err(ServiceStartFailure {
+                                        service: Box::new(self.clone()),
+                                        cause: Error::other(format!(
+                                            "wrapper exited with: {}",
+                                            status
+                                        )),
+                                        output: self.output_tail(),
+                                    });
+                                }
+                                pid
+                            }
+                            Err(e) => {
+                                error!("Failed to spawn commands: {:?}. Reason: {}", cmd, e);
+                                return Err(ServiceStartFailure {
+                                    service: Box::new(self.clone()),
+                                    cause: e,
+                                    output: self.output_tail(),
+                                });
+                            }
+                        }
+                    }
+                };
 
-                match get_group_by_name(&self.group()) {
-                    Some(gid) => {
-                        trace!("Setting service GID of valid group: {:?}", gid.name());
-                        cmd.gid(gid.gid());
+                match self.run_hook("after_start", &self.after_start) {
+                    Ok(_) => Ok(pid),
+                    Err(cause) => {
+                        Err(ServiceAfterStartFailure {
+                            service: Box::new(self.clone()),
+                            cause,
+                        })
                     }
-                    None => warn!("Username {} not found in system!", self.group()),
                 }
+            }
+            None => {
+                Err(ServiceNoStartDefined {
+                    service: Box::new(self.clone()),
+                })
+            }
+        }
+    }
+
 
-                match cmd.spawn() {
-                    Ok(mut child) => {
-                        let pid = child.id();
-                        warn!("NOTE: Service is supposed to go in background (daemonize)!");
-                        child.wait().expect("Service should spawn in background!");
-                        Ok(pid)
+    #[instrument]
+    fn stop_service(&self) -> Result<Blessing, Mortal> {
+        let pid = self.read_pid().ok();
+        let stopped = self.perform_stop();
+        event_log::record_result(STOP, &self.name(), pid, &stopped);
+        stopped
+    }
+
+
+    #[instrument]
+    fn perform_stop(&self) -> Result<Blessing, Mortal> {
+        // NOTE: forget foreground process first, so its watcher won't bring it back:
+        if self.foreground() {
+            if let Ok(mut managed) = FOREGROUND.lock() {
+                managed.remove(&self.name());
+            }
+        }
+
+        let stopped = match self.run_hook("stop", &self.stop) {
+            Ok(_) if self.stop.is_some() => {
+                Ok(OkServiceStopped {
+                    service: Box::new(self.clone()),
+                })
+            }
+            Ok(_) => self.death_watch(libc::SIGCONT),
+            Err(cause) => {
+                warn!(
+                    "{}. Falling back to death watch.",
+                    ServiceStopFailure {
+                        service: Box::new(self.clone()),
+                        cause,
                     }
-                    Err(e) => {
-                        error!("Failed to spawn commands: {:?}. Reason: {}", cmd, e);
-                        Err(ServiceStartFailure {
-                            service: self.clone(),
-                            cause: e,
-                        })
+                );
+                self.death_watch(libc::SIGCONT)
+            }
+        };
+        match stopped {
+            Ok(_) => debug!("Stopped service: {}", self.styled()),
+            Err(err) => return Err(err),
+        }
+        if self.foreground() {
+            match remove_file(self.pid_file()) {
+                Ok(_) => trace!("Removed pid file: {}", self.pid_file()),
+                Err(cause) => debug!("Couldn't remove pid file. Reason: {}", cause),
+            }
+        }
+
+        match self.run_hook("after_stop", &self.after_stop) {
+            Ok(_) => {
+                Ok(OkServiceStopped {
+                    service: Box::new(self.clone()),
+                })
+            }
+            Err(cause) => {
+                Err(ServiceAfterStopFailure {
+                    service: Box::new(self.clone()),
+                    cause,
+                })
+            }
+        }
+    }
+
+
+    #[instrument]
+    fn reload_service(&self) -> Result<Blessing, Mortal> {
+        // NOTE: nothing was performed without reload commands, so there's no event to record:
+        if self.reload.is_none() {
+            return Err(ServiceNoReloadDefined {
+                service: Box::new(self.clone()),
+            });
+        }
+        let reloaded = match self.run_hook("reload", &self.reload) {
+            Ok(_) => {
+                Ok(OkServiceReloaded {
+                    service: Box::new(self.clone()),
+                })
+            }
+            Err(cause) => {
+                Err(ServiceReloadFailure {
+                    service: Box::new(self.clone()),
+                    cause,
+                })
+            }
+        };
+        event_log::record_result(RELOAD, &self.name(), self.read_pid().ok(), &reloaded);
+        reloaded
+    }
+
+
+    #[instrument]
+    fn run_hook(&self, hook: &str, commands: &Option<String>) -> Result<(), Error> {
+        let commands = match commands {
+            Some(commands) => commands,
+            None => {
+                trace!("No '{}' commands defined for: {}", hook, self.styled());
+                return Ok(());
+            }
+        };
+        let mut cmd = Command::new(DEFAULT_SHELL);
+        cmd.arg("-c")
+            .arg(commands)
+            .env("PATH", DEFAULT_PATH)
+            .current_dir(self.work_dir())
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .process_group(0);
+        self.set_credentials(&mut cmd);
+
+        debug!("Running '{}' commands of: {}", hook, self.styled());
+        let mut child = cmd.spawn()?;
+        let stdout = self.spawn_reader(child.stdout.take())?;
+        let stderr = self.spawn_reader(child.stderr.take())?;
+        let deadline = Instant::now() + Duration::from_millis(self.hook_timeout());
+        let status = loop {
+            match child.try_wait()? {
+                Some(status) => break Some(status),
+                None if Instant::now() >= deadline => {
+                    // NOTE: kill whole process group - commands could spawn children:
+                    unsafe {
+                        libc::kill(-(child.id() as i32), libc::SIGKILL);
                     }
+                    child.wait()?;
+                    break None;
                 }
+                None => sleep(HOOK_POLL_INTERVAL),
+            }
+        };
+        self.write_output(&self.stdout_log(), &stdout.join().unwrap_or_default());
+        self.write_output(&self.stderr_log(), &stderr.join().unwrap_or_default());
+        match status {
+            Some(status) if status.success() => Ok(()),
+            Some(status) => {
+                Err(Error::other(format!(
+                    "'{}' commands exited with: {}",
+                    hook, status
+                )))
             }
             None => {
-                Err(ServiceNoStartDefined {
-                    service: self.clone(),
+                Err(Error::new(
+                    ErrorKind::TimedOut,
+                    format!(
+                        "'{}' commands killed after: {} ms",
+                        hook,
+                        self.hook_timeout()
+                    ),
+                ))
+            }
+        }
+    }
+
+
+    #[instrument(skip(reader))]
+    fn spawn_reader<R: Read + Send + 'static>(
+        &self,
+        reader: Option<R>,
+    ) -> Result<JoinHandle<Vec<u8>>, Error> {
+        Builder::new()
+            .name(format!("{}-hook", self.name()))
+            .spawn(move || {
+                let mut output = vec![];
+                if let Some(mut reader) = reader {
+                    if let Err(cause) = reader.read_to_end(&mut output) {
+                        debug!("Hook output closed. Reason: {}", cause);
+                    }
+                }
+                output
+            })
+    }
+
+
+    #[instrument(skip(cmd))]
+    fn redirect_output(&self, cmd: &mut Command) {
+        cmd.stdout(self.output_sink(&self.stdout_log()));
+        cmd.stderr(self.output_sink(&self.stderr_log()));
+    }
+
+
+    #[instrument]
+    fn output_sink(&self, path: &str) -> Stdio {
+        if path.is_empty() {
+            return Stdio::null();
+        }
+        // NOTE: output of foreground process is copied by Deities to rotated log file.
+        // Daemon has to write to log file directly - it outlives Deities and any pipe.
+        // Its log files are rotated by supervision instead (see: rotate_logs):
+        if self.foreground() {
+            return Stdio::piped();
+        }
+        match OpenOptions::new().create(true).append(true).open(path) {
+            Ok(file) => Stdio::from(file),
+            Err(cause) => {
+                error!("Can't open log file: {}. Reason: {}", path, cause);
+                Stdio::null()
+            }
+        }
+    }
+
+
+    #[instrument(skip(child))]
+    fn capture_output(&self, child: &mut Child) -> Vec<JoinHandle<()>> {
+        let mut copiers = vec![];
+        if let Some(stdout) = child.stdout.take() {
+            if let Some(copier) = self.spawn_copier("stdout", stdout, self.stdout_log()) {
+                copiers.push(copier);
+            }
+        }
+        if let Some(stderr) = child.stderr.take() {
+            if let Some(copier) = self.spawn_copier("stderr", stderr, self.stderr_log()) {
+                copiers.push(copier);
+            }
+        }
+        copiers
+    }
+
+
+    #[instrument(skip(reader))]
+    fn spawn_copier<R: Read + Send + 'static>(
+        &self,
+        stream: &str,
+        reader: R,
+        path: String,
+    ) -> Option<JoinHandle<()>> {
+        let sink = match RotatingFile::shared(
+            &path,
+            self.log_max_size() * 1024,
+            self.log_rotate_count(),
+        ) {
+            Ok(sink) => sink,
+            Err(cause) => {
+                error!("Can't open log file: {}. Reason: {}", path, cause);
+                return None;
+            }
+        };
+        match Builder::new()
+            .name(format!("{}-{}", self.name(), stream))
+            .spawn(move || copy_lines(reader, sink))
+        {
+            Ok(copier) => Some(copier),
+            Err(cause) => {
+                error!("Failed to spawn output copier thread! Reason: {}", cause);
+                None
+            }
+        }
+    }
+
+
+    #[instrument(skip(output))]
+    fn write_output(&self, path: &str, output: &[u8]) {
+        if path.is_empty() || output.is_empty() {
+            return;
+        }
+        match RotatingFile::shared(path, self.log_max_size() * 1024, self.log_rotate_count()) {
+            Ok(sink) => {
+                match sink.lock() {
+                    Ok(mut file) => {
+                        if let Err(cause) = file.write_all(output).and_then(|_| file.flush()) {
+                            error!("Failed to write output to: {}. Reason: {}", path, cause);
+                        }
+                    }
+                    Err(cause) => error!("Output file lock poisoned! Reason: {}", cause),
+                }
+            }
+            Err(cause) => error!("Can't open log file: {}. Reason: {}", path, cause),
+        }
+    }
+
+
+    #[instrument]
+    fn rotate_logs(&self) {
+        // NOTE: output of foreground process is rotated by its copiers on write:
+        if self.foreground() {
+            return;
+        }
+        let mut paths = vec![self.stdout_log(), self.stderr_log()];
+        paths.dedup();
+        for path in paths.iter().filter(|path| !path.is_empty()) {
+            let rotated = RotatingFile::shared(
+                path,
+                self.log_max_size() * 1024,
+                self.log_rotate_count(),
+            )
+            .and_then(|sink| {
+                sink.lock()
+                    .map_err(|cause| Error::other(cause.to_string()))?
+                    .rotate_oversized()
+            });
+            match rotated {
+                Ok(true) => info!("Rotated log file: {} of: {}", path, self.styled()),
+                Ok(false) => trace!("Log file: {} is below size limit", path),
+                Err(cause) => error!("Can't rotate log file: {}. Reason: {}", path, cause),
+            }
+        }
+    }
+
+
+    #[instrument(skip(cmd))]
+    fn set_credentials(&self, cmd: &mut Command) {
+        match get_user_by_name(&self.user()) {
+            Some(uid) => {
+                trace!("Setting service UID of valid user: {:?}", uid.name());
+                cmd.uid(uid.uid());
+            }
+            None => warn!("Username {} not found in system!", self.user()),
+        }
+
+        match get_group_by_name(&self.group()) {
+            Some(gid) => {
+                trace!("Setting service GID of valid group: {:?}", gid.name());
+                cmd.gid(gid.gid());
+            }
+            None => warn!("Username {} not found in system!", self.group()),
+        }
+    }
+
+
+    #[instrument]
+    fn spawn_foreground(&self, mut cmd: Command) -> Result<(u32, bool), Mortal> {
+        // NOTE: live managed process means that checks failed on running process - replace it:
+        let previous = match FOREGROUND.lock() {
+            Ok(mut managed) => {
+                match managed.get(&self.name()) {
+                    Some(process) if process.running => managed.remove(&self.name()),
+                    _ => None,
+                }
+            }
+            Err(cause) => {
+                return Err(SanityCheckFailure {
+                    message: format!("Foreground registry poisoned: {}", cause),
+                });
+            }
+        };
+        if let Some(process) = previous {
+            warn!(
+                "Replacing running foreground process: {} of: {}",
+                process.pid,
+                self.styled()
+            );
+            match self.death_watch(libc::SIGCONT) {
+                Ok(ok) => debug!("{}", ok),
+                Err(err) => warn!("{}", err),
+            }
+        }
+
+        let mut managed = match FOREGROUND.lock() {
+            Ok(managed) => managed,
+            Err(cause) => {
+                return Err(SanityCheckFailure {
+                    message: format!("Foreground registry poisoned: {}", cause),
+                });
+            }
+        };
+        if let Some(process) = managed.get(&self.name()) {
+            if process.running {
+                debug!(
+                    "Foreground process: {} of: {} was already started meanwhile",
+                    process.pid,
+                    self.styled()
+                );
+                return Ok((process.pid, false));
+            }
+        }
+        match cmd.spawn() {
+            Ok(mut child) => {
+                let pid = child.id();
+                self.capture_output(&mut child);
+                self.write_pid_file(pid);
+                managed.insert(
+                    self.name(),
+                    Foreground {
+                        pid,
+                        running: true,
+                    },
+                );
+                drop(managed);
+
+                let service = self.clone();
+                match Builder::new()
+                    .name(format!("{}-watch", self.name()))
+                    .spawn(move || service.watch_foreground(child))
+                {
+                    Ok(_) => debug!("Watching foreground pid: {} of: {}", pid, self.styled()),
+                    Err(cause) => error!("Failed to spawn watcher thread! Reason: {}", cause),
+                }
+                Ok((pid, true))
+            }
+            Err(e) => {
+                error!("Failed to spawn commands: {:?}. Reason: {}", cmd, e);
+                Err(ServiceStartFailure {
+                    service: Box::new(self.clone()),
+                    cause: e,
+                    output: self.output_tail(),
                 })
             }
         }
     }
+
+
+    #[instrument(skip(child))]
+    fn watch_foreground(&self, mut child: Child) {
+        let pid = child.id();
+        // NOTE: waitpid() - blocks only the watcher thread and reaps process when it exits:
+        match child.wait() {
+            Ok(status) => {
+                warn!(
+                    "Foreground process: {} of: {} exited with: {}",
+                    pid, self, status
+                );
+                event_log::record(
+                    EXIT,
+                    &self.name(),
+                    Some(pid as i32),
+                    status.success(),
+                    None,
+                    &format!("Foreground process exited with: {}", status),
+                );
+                if !status.success() {
+                    for line in self.output_tail() {
+                        warn!("{}: {}", self.name(), line)
+                    }
+                }
+            }
+            Err(cause) => {
+                error!(
+                    "Failed to wait for pid: {} of: {}. Reason: {}",
+                    pid, self, cause
+                )
+            }
+        }
+
+        // NOTE: registry entry replaced or removed means that process was taken care of:
+        let owned = match FOREGROUND.lock() {
+            Ok(mut managed) => {
+                match managed.get_mut(&self.name()) {
+                    Some(process) if process.pid == pid => {
+                        process.running = false;
+                        true
+                    }
+                    _ => false,
+                }
+            }
+            Err(_) => false,
+        };
+        if !owned {
+            debug!("Foreground process: {} of: {} is no longer managed", pid, self);
+            return;
+        }
+        match remove_file(self.pid_file()) {
+            Ok(_) => trace!("Removed pid file: {}", self.pid_file()),
+            Err(cause) => {
+                debug!(
+                    "Couldn't remove pid file: {}. Reason: {}",
+                    self.pid_file(),
+                    cause
+                )
+            }
+        }
+        // NOTE: no restart here - supervision considers service down on its next check
+        // (see: foreground_exited) and brings it back within its restart limits
+    }
+
+
+    #[instrument]
+    fn write_pid_file(&self, pid: u32) {
+        match File::create(self.pid_file()) {
+            Ok(mut file) => {
+                match file.write_all(format!("{}\n", pid).as_bytes()) {
+                    Ok(_) => trace!("Pid: {} written to: {}", pid, self.pid_file()),
+                    Err(cause) => error!("Pid file write error! Reason: {}", cause),
+                }
+            }
+            Err(cause) => {
+                error!(
+                    "Can't create pid file: {}. Reason: {}",
+                    self.pid_file(),
+                    cause
+                )
+            }
+        }
+    }
 }